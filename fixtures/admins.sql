@@ -1,2 +1,3 @@
-INSERT INTO admin (id, key)
-VALUES (1, 'ef8a53f0b0cb43dd764fe16a442752d6');
+-- key: ef8a53f0b0cb43dd764fe16a442752d6
+INSERT INTO admin (id, key_hash, salt, created_at)
+VALUES (1, '508f690e7add847034c58c092d4e4c3c04dd9f0d612011cce5703a798b80ec0f', '9c4e1b7a2f6d8e0c3a5b7d9f1e2c4a6b', datetime('now'));
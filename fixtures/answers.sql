@@ -0,0 +1,2 @@
+INSERT INTO project (dirname, prompt, answers)
+VALUES ('', 'Which image is sharper?', '["equal","cannot_tell"]');
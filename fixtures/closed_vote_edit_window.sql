@@ -0,0 +1,2 @@
+INSERT INTO project (dirname, vote_edit_window)
+VALUES ('folder_b/folder_c', 0);
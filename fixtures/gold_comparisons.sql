@@ -0,0 +1,8 @@
+INSERT INTO project (dirname, gold_rate)
+VALUES ('', 1.0);
+
+INSERT INTO comparison (id, dirname, images, created_by, expected_vote_value)
+VALUES (x'9b8a7c6d5e4f40318a2b1c0d9e8f7a6b', '', 'image%20A.png///image%20A%20degraded.png', 1, '/static/images/image%20A.png');
+
+INSERT INTO vote (comparison_id, user_id, vote_value, ip_addr, gold_passed)
+VALUES (x'9b8a7c6d5e4f40318a2b1c0d9e8f7a6b', x'ac01a03d75e34244a33ba2324b8784f1', '/static/images/image%20A%20degraded.png', '127.0.0.1', 0);
@@ -0,0 +1,3 @@
+-- plaintext key, as stored before keys were hashed
+INSERT INTO admin (id, key_hash, created_at)
+VALUES (2, '0f1e2d3c4b5a69788796a5b4c3d2e1f0', datetime('now'));
@@ -0,0 +1,8 @@
+INSERT INTO comparison (id, dirname, images, created_by)
+VALUES (x'5a0c3e2b8d7f4e6a9b1c2d3e4f5a6b01', 'folder_a', 'folder_a/image%201.png///folder_a/image%202.png', 1);
+
+INSERT INTO comparison (id, dirname, images, created_by)
+VALUES (x'5a0c3e2b8d7f4e6a9b1c2d3e4f5a6b02', 'folder_a', 'folder_a/image%202.png///folder_a/image%201.png', 1);
+
+INSERT INTO comparison (id, dirname, images, created_by)
+VALUES (x'5a0c3e2b8d7f4e6a9b1c2d3e4f5a6b03', 'folder_a', 'folder_a/image%201.png///folder_a/image%209.png', 1);
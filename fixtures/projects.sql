@@ -0,0 +1,2 @@
+INSERT INTO project (dirname, selection_strategy)
+VALUES ('', 'balanced');
@@ -0,0 +1,23 @@
+INSERT INTO project (dirname, selection_strategy)
+VALUES ('folder_a', 'informative');
+
+INSERT INTO comparison (id, dirname, images, created_by)
+VALUES (x'2b1c5c4e0a7d4c1f9a3e6d2f8b7a9c01', 'folder_a', 'folder_a/image%201.png///folder_a/image%202.png', 1);
+
+INSERT INTO comparison (id, dirname, images, created_by)
+VALUES (x'2b1c5c4e0a7d4c1f9a3e6d2f8b7a9c02', 'folder_a', 'folder_a/image%201.png///folder_a/image%203.png', 1);
+
+INSERT INTO comparison (id, dirname, images, created_by)
+VALUES (x'2b1c5c4e0a7d4c1f9a3e6d2f8b7a9c03', 'folder_a', 'folder_a/image%203.png///folder_a/image%201.png', 1);
+
+INSERT INTO vote (comparison_id, user_id, vote_value, ip_addr)
+VALUES (x'2b1c5c4e0a7d4c1f9a3e6d2f8b7a9c02', x'3fa85f6457174562b3fc2c963f66afa6', '/static/images/folder_a/image%201.png', '127.0.0.1');
+
+INSERT INTO vote (comparison_id, user_id, vote_value, ip_addr)
+VALUES (x'2b1c5c4e0a7d4c1f9a3e6d2f8b7a9c02', x'ac01a03d75e34244a33ba2324b8784f1', '/static/images/folder_a/image%201.png', '127.0.0.1');
+
+INSERT INTO vote (comparison_id, user_id, vote_value, ip_addr)
+VALUES (x'2b1c5c4e0a7d4c1f9a3e6d2f8b7a9c03', x'3fa85f6457174562b3fc2c963f66afa6', '/static/images/folder_a/image%201.png', '127.0.0.1');
+
+INSERT INTO vote (comparison_id, user_id, vote_value, ip_addr)
+VALUES (x'2b1c5c4e0a7d4c1f9a3e6d2f8b7a9c03', x'ac01a03d75e34244a33ba2324b8784f1', '/static/images/folder_a/image%201.png', '127.0.0.1');
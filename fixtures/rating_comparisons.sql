@@ -0,0 +1,8 @@
+INSERT INTO project (dirname, rating_scale)
+VALUES ('folder_b/folder_c', 5);
+
+INSERT INTO comparison (id, dirname, images, created_by)
+VALUES (x'2e4f6a8c0b1d4f3e9a7c5b3d1f0e2a4c', 'folder_b/folder_c', 'folder_b/folder_c/image%204.png', 1);
+
+INSERT INTO vote (comparison_id, user_id, vote_value, ip_addr)
+VALUES (x'2e4f6a8c0b1d4f3e9a7c5b3d1f0e2a4c', x'ac01a03d75e34244a33ba2324b8784f1', '4', '127.0.0.1');
@@ -0,0 +1,2 @@
+INSERT INTO project (dirname, allow_repeat_votes)
+VALUES ('', 1);
@@ -0,0 +1,5 @@
+INSERT INTO comparison (id, dirname, images, created_by, retired_at)
+VALUES (x'5e7d1c2b3a4940f8b6c5d4e3f2a1b0c9', 'folder_b/folder_c', 'folder_b/folder_c/image%205.png///folder_b/folder_c/image%206.png', 1, datetime('now'));
+
+INSERT INTO vote (comparison_id, user_id, vote_value, ip_addr)
+VALUES (x'5e7d1c2b3a4940f8b6c5d4e3f2a1b0c9', x'3fa85f6457174562b3fc2c963f66afa6', '/static/images/folder_b/folder_c/image%206.png', '127.0.0.1');
@@ -0,0 +1,14 @@
+-- key: a1b2c3d4e5f60718293a4b5c6d7e8f90
+INSERT INTO admin (id, key_hash, salt, created_at, role)
+VALUES (3, '4285b4b7b46ff1957a86f46db72928ee45ef954d391fa8a991d6dd1c92e6276d', '1d3f5a7c9e0b2d4f6a8c0e1b3d5f7a9c', datetime('now'), 'viewer');
+
+-- key: b2c3d4e5f60718293a4b5c6d7e8f90a1
+INSERT INTO admin (id, key_hash, salt, created_at, role)
+VALUES (4, 'eef0d72695c9b65ab654b5e597cc2ae53a9cee6e07b956727323408d7d9d74d4', '2e4a6c8e0f1b3d5a7c9e1f2b4d6a8c0e', datetime('now'), 'curator');
+
+-- key: c3d4e5f60718293a4b5c6d7e8f90a1b2
+INSERT INTO admin (id, key_hash, salt, created_at, role)
+VALUES (5, '9abff854b55d043d1856d1f826b6a71c88e9f005aa34da3c1a52f446b6b3f1fd', '3f5b7d9f1a2c4e6b8d0f2a3c5e7b9d1f', datetime('now'), 'owner');
+
+INSERT INTO admin_dirname (admin_id, dirname)
+VALUES (4, 'folder_a'), (5, 'folder_b');
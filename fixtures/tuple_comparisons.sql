@@ -0,0 +1,5 @@
+INSERT INTO comparison (id, dirname, images, created_by)
+VALUES (x'5c1e8f0a2b7d4e96a3f1c0d9e8b7a6f5', 'folder_a', 'folder_a/image%201.png///folder_a/image%202.png///folder_a/image%203.png', 1);
+
+INSERT INTO vote (comparison_id, user_id, vote_value, ip_addr)
+VALUES (x'5c1e8f0a2b7d4e96a3f1c0d9e8b7a6f5', x'ac01a03d75e34244a33ba2324b8784f1', '{"best":"/static/images/folder_a/image%202.png","worst":"/static/images/folder_a/image%201.png"}', '127.0.0.1');
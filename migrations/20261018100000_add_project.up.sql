@@ -0,0 +1,4 @@
+CREATE TABLE project (
+	dirname TEXT PRIMARY KEY NOT NULL,
+	selection_strategy TEXT NOT NULL DEFAULT 'random'
+) WITHOUT ROWID;
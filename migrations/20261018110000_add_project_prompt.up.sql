@@ -0,0 +1 @@
+ALTER TABLE project ADD COLUMN prompt TEXT;
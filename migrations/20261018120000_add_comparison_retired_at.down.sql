@@ -0,0 +1 @@
+ALTER TABLE comparison DROP COLUMN retired_at;
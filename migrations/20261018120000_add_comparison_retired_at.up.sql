@@ -0,0 +1 @@
+ALTER TABLE comparison ADD COLUMN retired_at TEXT;
@@ -0,0 +1,5 @@
+ALTER TABLE admin DROP COLUMN revoked_at;
+ALTER TABLE admin DROP COLUMN last_used_at;
+ALTER TABLE admin DROP COLUMN created_at;
+ALTER TABLE admin DROP COLUMN salt;
+ALTER TABLE admin RENAME COLUMN key_hash TO key;
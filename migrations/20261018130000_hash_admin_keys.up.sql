@@ -0,0 +1,7 @@
+ALTER TABLE admin RENAME COLUMN key TO key_hash;
+-- rows without a `salt` still hold a plaintext key, hashed on ignite
+ALTER TABLE admin ADD COLUMN salt TEXT;
+ALTER TABLE admin ADD COLUMN created_at TEXT;
+ALTER TABLE admin ADD COLUMN last_used_at TEXT;
+ALTER TABLE admin ADD COLUMN revoked_at TEXT;
+UPDATE admin SET created_at = datetime('now');
@@ -0,0 +1,2 @@
+DROP TABLE admin_dirname;
+ALTER TABLE admin DROP COLUMN role;
@@ -0,0 +1,10 @@
+-- existing admins keep full access
+ALTER TABLE admin ADD COLUMN role TEXT NOT NULL DEFAULT 'owner';
+-- an admin without rows here can access every dirname
+CREATE TABLE admin_dirname (
+	admin_id INTEGER NOT NULL,
+	dirname TEXT NOT NULL,
+	PRIMARY KEY (admin_id, dirname),
+	FOREIGN KEY(admin_id) REFERENCES admin(id)
+		ON DELETE CASCADE
+) WITHOUT ROWID;
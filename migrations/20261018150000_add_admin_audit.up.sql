@@ -0,0 +1,16 @@
+CREATE TABLE admin_audit (
+	id INTEGER PRIMARY KEY,
+	admin_id INTEGER NOT NULL,
+	method TEXT NOT NULL,
+	route TEXT NOT NULL,
+	path TEXT NOT NULL,
+	-- JSON object of the path and query parameters
+	parameters TEXT NOT NULL,
+	status INTEGER NOT NULL,
+	affected_rows INTEGER,
+	ip_addr TEXT,
+	request_id INTEGER NOT NULL,
+	created_at TEXT NOT NULL DEFAULT (datetime('now')),
+	FOREIGN KEY(admin_id) REFERENCES admin(id)
+);
+CREATE INDEX admin_audit_admin_id ON admin_audit(admin_id);
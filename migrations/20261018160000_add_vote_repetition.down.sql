@@ -0,0 +1,3 @@
+ALTER TABLE project DROP COLUMN allow_repeat_votes;
+DROP INDEX vote_comparison_user_repetition;
+ALTER TABLE vote DROP COLUMN repetition;
@@ -0,0 +1,12 @@
+-- 0 for the first vote of a user on a comparison, then 1, 2, ... for the
+-- repeat votes allowed by `project.allow_repeat_votes`
+ALTER TABLE vote ADD COLUMN repetition INTEGER NOT NULL DEFAULT 0;
+UPDATE vote SET repetition = (
+	SELECT COUNT(*) FROM vote AS earlier
+	WHERE earlier.comparison_id = vote.comparison_id
+		AND earlier.user_id = vote.user_id
+		AND earlier.id < vote.id
+);
+CREATE UNIQUE INDEX vote_comparison_user_repetition
+	ON vote(comparison_id, user_id, repetition);
+ALTER TABLE project ADD COLUMN allow_repeat_votes INTEGER NOT NULL DEFAULT 0;
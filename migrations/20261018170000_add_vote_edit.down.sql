@@ -0,0 +1,7 @@
+ALTER TABLE project DROP COLUMN vote_edit_window;
+DROP TABLE vote_edit;
+DELETE FROM vote WHERE retracted_at IS NOT NULL;
+DROP INDEX vote_comparison_user_repetition;
+CREATE UNIQUE INDEX vote_comparison_user_repetition
+	ON vote(comparison_id, user_id, repetition);
+ALTER TABLE vote DROP COLUMN retracted_at;
@@ -0,0 +1,22 @@
+-- retracted votes are kept, but no longer count anywhere, so the `user` can
+-- vote again on the comparison
+ALTER TABLE vote ADD COLUMN retracted_at TEXT;
+DROP INDEX vote_comparison_user_repetition;
+CREATE UNIQUE INDEX vote_comparison_user_repetition
+	ON vote(comparison_id, user_id, repetition)
+	WHERE retracted_at IS NULL;
+-- one row per change of a vote, holding its `vote_value` before the change,
+-- so the first row of a vote holds the answer originally given
+CREATE TABLE vote_edit (
+	id INTEGER PRIMARY KEY,
+	vote_id INTEGER NOT NULL,
+	action TEXT NOT NULL,
+	vote_value TEXT NOT NULL,
+	created_at TEXT NOT NULL DEFAULT (datetime('now')),
+	FOREIGN KEY(vote_id) REFERENCES vote(id)
+		ON DELETE CASCADE
+);
+CREATE INDEX vote_edit_vote_id ON vote_edit(vote_id);
+-- seconds after casting a vote during which its `user` can still change or
+-- retract it, 0 to disallow
+ALTER TABLE project ADD COLUMN vote_edit_window INTEGER NOT NULL DEFAULT 300;
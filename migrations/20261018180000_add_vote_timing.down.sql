@@ -0,0 +1,5 @@
+ALTER TABLE vote DROP COLUMN device_pixel_ratio;
+ALTER TABLE vote DROP COLUMN viewport_height;
+ALTER TABLE vote DROP COLUMN viewport_width;
+ALTER TABLE vote DROP COLUMN response_time_ms;
+DROP TABLE assignment;
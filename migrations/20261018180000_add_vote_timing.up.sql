@@ -0,0 +1,16 @@
+-- when a comparison was last handed out to a user, to time their vote on it;
+-- the row is consumed by that vote
+CREATE TABLE assignment (
+	comparison_id BLOB NOT NULL,
+	user_id BLOB NOT NULL,
+	issued_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f', 'now')),
+	PRIMARY KEY(comparison_id, user_id),
+	FOREIGN KEY(comparison_id) REFERENCES comparison(id)
+		ON DELETE CASCADE,
+	FOREIGN KEY(user_id) REFERENCES user(id)
+		ON DELETE CASCADE
+);
+ALTER TABLE vote ADD COLUMN response_time_ms INTEGER;
+ALTER TABLE vote ADD COLUMN viewport_width INTEGER;
+ALTER TABLE vote ADD COLUMN viewport_height INTEGER;
+ALTER TABLE vote ADD COLUMN device_pixel_ratio REAL;
@@ -0,0 +1,3 @@
+-- how sure the user was of their vote, from 1 (coin flip) to 5 (clearly)
+ALTER TABLE vote ADD COLUMN confidence INTEGER
+	CHECK (confidence BETWEEN 1 AND 5);
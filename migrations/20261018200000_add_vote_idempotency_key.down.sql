@@ -0,0 +1,3 @@
+DROP INDEX vote_user_idempotency_key;
+ALTER TABLE vote DROP COLUMN client_created_at;
+ALTER TABLE vote DROP COLUMN idempotency_key;
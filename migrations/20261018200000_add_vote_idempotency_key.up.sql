@@ -0,0 +1,7 @@
+-- sent by clients queueing votes while offline, so a vote submitted twice is
+-- only stored once, along with when the client recorded it
+ALTER TABLE vote ADD COLUMN idempotency_key TEXT;
+ALTER TABLE vote ADD COLUMN client_created_at TEXT;
+CREATE UNIQUE INDEX vote_user_idempotency_key
+	ON vote(user_id, idempotency_key)
+	WHERE idempotency_key IS NOT NULL;
@@ -0,0 +1,17 @@
+-- responses to requests sent with an `Idempotency-Key` header, replayed when
+-- the same caller repeats the key on the same route with the same request
+-- body; the key is reserved along with the rows the request creates, before
+-- its response is known
+CREATE TABLE idempotent_response (
+	route TEXT NOT NULL,
+	-- the `user` casting a vote, or the address of the client creating one
+	caller TEXT NOT NULL,
+	idempotency_key TEXT NOT NULL,
+	-- SHA-256 of the request body, hex encoded
+	request_hash TEXT NOT NULL,
+	-- the status and JSON body of the response, null until it is known
+	status INTEGER,
+	body TEXT,
+	created_at TEXT NOT NULL DEFAULT (datetime('now')),
+	PRIMARY KEY(route, caller, idempotency_key)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE project DROP COLUMN rating_labels;
+ALTER TABLE project DROP COLUMN rating_scale;
@@ -0,0 +1,5 @@
+-- points of the scale single-image comparisons are rated on, starting at 1,
+-- and the labels shown for them, as a JSON array of one label per point or
+-- none at all
+ALTER TABLE project ADD COLUMN rating_scale INTEGER NOT NULL DEFAULT 7;
+ALTER TABLE project ADD COLUMN rating_labels TEXT NOT NULL DEFAULT '[]';
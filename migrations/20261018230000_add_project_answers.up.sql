@@ -0,0 +1,3 @@
+-- answers users can give besides picking images, as a JSON array
+ALTER TABLE project ADD COLUMN answers TEXT NOT NULL
+	DEFAULT '["equal","different"]';
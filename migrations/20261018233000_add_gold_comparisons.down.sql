@@ -0,0 +1,4 @@
+DROP INDEX vote_user_gold_passed;
+ALTER TABLE project DROP COLUMN gold_rate;
+ALTER TABLE vote DROP COLUMN gold_passed;
+ALTER TABLE comparison DROP COLUMN expected_vote_value;
@@ -0,0 +1,9 @@
+-- gold comparisons have a known correct answer, served to users at the
+-- gold rate of their dirname to check they are paying attention; votes on
+-- them record whether they gave that answer
+ALTER TABLE comparison ADD COLUMN expected_vote_value TEXT;
+ALTER TABLE vote ADD COLUMN gold_passed INTEGER;
+ALTER TABLE project ADD COLUMN gold_rate REAL NOT NULL DEFAULT 0.0;
+CREATE INDEX vote_user_gold_passed
+	ON vote(user_id, gold_passed)
+	WHERE gold_passed IS NOT NULL;
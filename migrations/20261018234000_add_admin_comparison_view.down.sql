@@ -0,0 +1 @@
+DROP VIEW admin_comparison;
@@ -0,0 +1,14 @@
+-- the comparisons each admin can access: those in one of its dirnames or
+-- below, or every comparison for an admin without rows in admin_dirname
+CREATE VIEW admin_comparison AS
+SELECT admin.id AS admin_id, comparison.id AS comparison_id
+FROM admin CROSS JOIN comparison
+WHERE NOT EXISTS (
+	SELECT 1 FROM admin_dirname WHERE admin_dirname.admin_id = admin.id
+) OR EXISTS (
+	SELECT 1 FROM admin_dirname
+	WHERE admin_dirname.admin_id = admin.id
+		AND (comparison.dirname = admin_dirname.dirname
+			OR substr(comparison.dirname, 1, length(admin_dirname.dirname) + 1)
+				= admin_dirname.dirname || '/')
+);
@@ -1,4 +1,5 @@
 use rocket::{
+    form,
     http::Status,
     outcome::{
         try_outcome,
@@ -13,13 +14,30 @@ use rocket::{
     State,
 };
 use rocket_db_pools::Connection;
+use serde::Serialize;
 
 use super::{
+    key::{
+        AdminAccess,
+        AdminInfo,
+        AdminKey,
+    },
+    pairing::PairingOptions,
     Admin,
+    AdminRole,
     Comparison,
+    Curator,
+    DirnamePreview,
+    GenerationError,
+    GenerationOptions,
+    GenerationReport,
+    Owner,
+    TransactionScope,
+    Viewer,
 };
 use crate::{
     api::{
+        audit::AuditTrail,
         QueryError,
         RequestId,
     },
@@ -28,26 +46,218 @@ use crate::{
     StaticDir,
 };
 
-#[post("/admin/comparison")]
+/// Either the `comparison`s generated, the full report when run
+/// incrementally, retiring missing images or with one transaction per
+/// dirname, or the per-dirname preview of a dry run.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum Generated<'r> {
+    Comparisons(Vec<Comparison<'r>>),
+    Report(GenerationReport<'r>),
+    Preview(Vec<DirnamePreview<'r>>),
+}
+
+#[allow(clippy::too_many_arguments)]
+#[post(
+    "/admin/comparison?<incremental>&<retire_missing>&<transaction>&<dry_run>&\
+     <pairing..>"
+)]
 pub(crate) async fn generate_comparisons<'r>(
-    admin: Admin,
+    admin: Curator,
+    incremental: bool,
+    retire_missing: bool,
+    transaction: form::Result<'_, TransactionScope>,
+    dry_run: bool,
+    pairing: PairingOptions,
     request_id: &RequestId,
     static_dir: &State<StaticDir>,
+    audit: &AuditTrail,
     mut connection: Connection<DbPool>,
-) -> (Status, Json<ResponseBody<Vec<Comparison<'r>>, QueryError>>) {
-    let comparisons = super::generate_comparisons_from_static_dir(
+) -> (Status, Json<ResponseBody<Generated<'r>, GenerationError>>) {
+    let transaction = match TransactionScope::from_query(transaction) {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            let error = GenerationError::from(error);
+            return (
+                error.default_status(),
+                Json((request_id, Err(error)).into()),
+            );
+        },
+    };
+    let options = GenerationOptions {
+        incremental,
+        retire_missing,
+        transaction,
+    };
+
+    if dry_run {
+        let plans = super::plan_comparisons_from_static_dir(
+            &admin,
+            &pairing,
+            &options,
+            static_dir,
+            &mut **connection,
+        )
+        .await;
+
+        return match plans.map_err(GenerationError::from) {
+            Err(error) => {
+                (error.default_status(), Json((request_id, Err(error)).into()))
+            },
+            Ok(plans) => {
+                let preview = plans.into_iter().map(Into::into).collect();
+                (
+                    Status::Ok,
+                    Json((request_id, Ok(Generated::Preview(preview))).into()),
+                )
+            },
+        };
+    }
+
+    let report = super::generate_comparisons_from_static_dir(
         &admin,
+        &pairing,
+        &options,
         static_dir,
         &mut **connection,
     )
     .await;
 
-    match comparisons {
+    if let Ok(report) = &report {
+        audit.add_affected_rows(report.created.len() + report.retired.len());
+    }
+
+    match report {
         Err(error) => {
             (error.default_status(), Json((request_id, Err(error)).into()))
         },
-        Ok(comparisons) => {
-            (Status::Created, Json((request_id, Ok(comparisons)).into()))
+        Ok(report)
+            if incremental
+                || retire_missing
+                || transaction == TransactionScope::Dirname =>
+        {
+            (
+                Status::Created,
+                Json((request_id, Ok(Generated::Report(report))).into()),
+            )
+        },
+        Ok(report) => (
+            Status::Created,
+            Json(
+                (request_id, Ok(Generated::Comparisons(report.created))).into(),
+            ),
+        ),
+    }
+}
+
+#[get("/admin/admins")]
+pub(crate) async fn list_admins(
+    admin: Owner,
+    request_id: &RequestId,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<Vec<AdminInfo>, QueryError>>) {
+    if let Err(error) = admin.check_unscoped_owner() {
+        return (error.default_status(), Json((request_id, Err(error)).into()));
+    }
+
+    match super::key::list_admins(&mut **connection).await {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(admins) => (Status::Ok, Json((request_id, Ok(admins)).into())),
+    }
+}
+
+#[post("/admin/admins", format = "application/json", data = "<access>")]
+pub(crate) async fn create_admin(
+    admin: Owner,
+    access: Json<AdminAccess>,
+    request_id: &RequestId,
+    audit: &AuditTrail,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<AdminKey, QueryError>>) {
+    if let Err(error) = admin.check_unscoped_owner() {
+        return (error.default_status(), Json((request_id, Err(error)).into()));
+    }
+
+    match super::key::create_admin(&access, &mut **connection).await {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(key) => {
+            audit.add_affected_rows(1);
+            (Status::Created, Json((request_id, Ok(key)).into()))
+        },
+    }
+}
+
+#[put("/admin/admins/<id>", format = "application/json", data = "<access>")]
+pub(crate) async fn update_admin_access(
+    admin: Owner,
+    id: i64,
+    access: Json<AdminAccess>,
+    request_id: &RequestId,
+    audit: &AuditTrail,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<AdminInfo, QueryError>>) {
+    if let Err(error) = admin.check_unscoped_owner() {
+        return (error.default_status(), Json((request_id, Err(error)).into()));
+    }
+
+    match super::key::update_admin_access(id, &access, &mut **connection).await
+    {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(admin) => {
+            audit.add_affected_rows(1);
+            (Status::Ok, Json((request_id, Ok(admin)).into()))
+        },
+    }
+}
+
+#[post("/admin/admins/<id>/rotate")]
+pub(crate) async fn rotate_admin_key(
+    admin: Owner,
+    id: i64,
+    request_id: &RequestId,
+    audit: &AuditTrail,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<AdminKey, QueryError>>) {
+    if let Err(error) = admin.check_unscoped_owner() {
+        return (error.default_status(), Json((request_id, Err(error)).into()));
+    }
+
+    match super::key::rotate_admin_key(id, &mut **connection).await {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(key) => {
+            audit.add_affected_rows(1);
+            (Status::Ok, Json((request_id, Ok(key)).into()))
+        },
+    }
+}
+
+#[delete("/admin/admins/<id>")]
+pub(crate) async fn revoke_admin(
+    admin: Owner,
+    id: i64,
+    request_id: &RequestId,
+    audit: &AuditTrail,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<AdminInfo, QueryError>>) {
+    if let Err(error) = admin.check_unscoped_owner() {
+        return (error.default_status(), Json((request_id, Err(error)).into()));
+    }
+
+    match super::key::revoke_admin(id, &mut **connection).await {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(admin) => {
+            audit.add_affected_rows(1);
+            (Status::Ok, Json((request_id, Ok(admin)).into()))
         },
     }
 }
@@ -69,11 +279,64 @@ impl<'r> FromRequest<'r> for Admin {
             None => Outcome::Forward(Status::Unauthorized),
             Some(key) => {
                 let key = key.trim_start_matches("Bearer").trim();
-                super::get_admin(key, &mut **connection)
-                    .await
-                    .ok()
-                    .or_forward(Status::Unauthorized)
+                let admin = super::get_admin(key, &mut **connection).await;
+                if let Ok(admin) = &admin {
+                    AuditTrail::of(request).set_admin(admin.id);
+                }
+
+                admin.ok().or_forward(Status::Unauthorized)
             },
         }
     }
 }
+
+/// Gets the `Admin` of the request, forwarding with 403 Forbidden when its
+/// role is below `role`.
+async fn admin_with_role(
+    request: &Request<'_>,
+    role: AdminRole,
+) -> Outcome<Admin, ()> {
+    let admin = try_outcome!(request.guard::<Admin>().await);
+
+    match admin.role >= role {
+        true => Outcome::Success(admin),
+        false => Outcome::Forward(Status::Forbidden),
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Viewer {
+    type Error = ();
+
+    async fn from_request(
+        request: &'r Request<'_>,
+    ) -> Outcome<Self, Self::Error> {
+        admin_with_role(request, AdminRole::Viewer)
+            .await
+            .map(Viewer)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Curator {
+    type Error = ();
+
+    async fn from_request(
+        request: &'r Request<'_>,
+    ) -> Outcome<Self, Self::Error> {
+        admin_with_role(request, AdminRole::Curator)
+            .await
+            .map(Curator)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Owner {
+    type Error = ();
+
+    async fn from_request(
+        request: &'r Request<'_>,
+    ) -> Outcome<Self, Self::Error> {
+        admin_with_role(request, AdminRole::Owner).await.map(Owner)
+    }
+}
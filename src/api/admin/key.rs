@@ -0,0 +1,345 @@
+use chrono::{
+    DateTime,
+    Utc,
+};
+use rand::RngCore;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sha2::{
+    Digest,
+    Sha256,
+};
+use sqlx::{
+    Connection,
+    SqliteConnection,
+};
+use subtle::ConstantTimeEq;
+
+use super::AdminRole;
+use crate::api::QueryError;
+
+/// A newly created or rotated admin key. The key itself is only ever
+/// returned here, the database stores a salted hash of it.
+#[derive(Serialize)]
+pub(crate) struct AdminKey {
+    pub(crate) id: i64,
+    pub(crate) key: String,
+}
+
+#[derive(Serialize)]
+pub(crate) struct AdminInfo {
+    pub(crate) id: i64,
+    pub(crate) role: AdminRole,
+    pub(crate) dirnames: Vec<String>,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) last_used_at: Option<DateTime<Utc>>,
+    pub(crate) revoked_at: Option<DateTime<Utc>>,
+}
+
+/// The role of an `admin` and the dirnames it is restricted to, none
+/// meaning every dirname.
+#[derive(Deserialize)]
+pub(crate) struct AdminAccess {
+    pub(crate) role: AdminRole,
+    #[serde(default)]
+    pub(crate) dirnames: Vec<String>,
+}
+
+/// Returns 16 random bytes, hex encoded.
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+pub(crate) fn hash_key(salt: &str, key: &str) -> String {
+    let hash = Sha256::new()
+        .chain_update(salt.as_bytes())
+        .chain_update(key.as_bytes())
+        .finalize();
+    hex::encode(hash)
+}
+
+/// Checks `key` against a stored hash, in constant time.
+pub(crate) fn verify_key(key: &str, salt: &str, key_hash: &str) -> bool {
+    hash_key(salt, key)
+        .as_bytes()
+        .ct_eq(key_hash.as_bytes())
+        .into()
+}
+
+pub(crate) async fn create_admin(
+    access: &AdminAccess,
+    connection: &mut SqliteConnection,
+) -> Result<AdminKey, QueryError> {
+    let key = generate_secret();
+    let salt = generate_secret();
+    let key_hash = hash_key(&salt, &key);
+    let role = access.role.to_string();
+
+    let mut transaction = connection.begin().await?;
+
+    let id = sqlx::query_scalar!(
+        "INSERT INTO admin (key_hash, salt, created_at, role) VALUES (?, ?, \
+         datetime('now'), ?) RETURNING id",
+        key_hash,
+        salt,
+        role,
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    set_admin_dirnames(id, &access.dirnames, &mut transaction).await?;
+    transaction.commit().await?;
+
+    Ok(AdminKey { id, key })
+}
+
+/// Changes the role and dirnames of a non-revoked `admin`.
+pub(crate) async fn update_admin_access(
+    id: i64,
+    access: &AdminAccess,
+    connection: &mut SqliteConnection,
+) -> Result<AdminInfo, QueryError> {
+    let mut transaction = connection.begin().await?;
+
+    if access.role != AdminRole::Owner || !access.dirnames.is_empty() {
+        check_other_owner(id, "demoted", &mut transaction).await?;
+    }
+
+    let role = access.role.to_string();
+    sqlx::query_scalar!(
+        "UPDATE admin SET role = ? WHERE id = ? AND revoked_at IS NULL \
+         RETURNING id",
+        role,
+        id,
+    )
+    .fetch_one(&mut *transaction)
+    .await
+    .map_err(|error| match error {
+        sqlx::Error::RowNotFound => QueryError::RowNotFound(
+            "Active `admin` with requested id not found".to_string(),
+        ),
+        error => error.into(),
+    })?;
+
+    set_admin_dirnames(id, &access.dirnames, &mut transaction).await?;
+    let admin = get_admin_info(id, &mut transaction).await?;
+    transaction.commit().await?;
+
+    Ok(admin)
+}
+
+/// Replaces the key of a non-revoked `admin`, invalidating the old one.
+pub(crate) async fn rotate_admin_key(
+    id: i64,
+    connection: &mut SqliteConnection,
+) -> Result<AdminKey, QueryError> {
+    let key = generate_secret();
+    let salt = generate_secret();
+    let key_hash = hash_key(&salt, &key);
+
+    sqlx::query_scalar!(
+        "UPDATE admin SET key_hash = ?, salt = ? WHERE id = ? AND revoked_at \
+         IS NULL RETURNING id",
+        key_hash,
+        salt,
+        id,
+    )
+    .fetch_one(connection)
+    .await
+    .map_err(|error| match error {
+        sqlx::Error::RowNotFound => QueryError::RowNotFound(
+            "Active `admin` with requested id not found".to_string(),
+        ),
+        error => error.into(),
+    })?;
+
+    Ok(AdminKey { id, key })
+}
+
+/// Revokes the key of an `admin`. The row is kept, since the
+/// `comparison`s it created reference it.
+pub(crate) async fn revoke_admin(
+    id: i64,
+    connection: &mut SqliteConnection,
+) -> Result<AdminInfo, QueryError> {
+    check_other_owner(id, "revoked", &mut *connection).await?;
+
+    sqlx::query_scalar!(
+        "UPDATE admin SET revoked_at = datetime('now') WHERE id = ? AND \
+         revoked_at IS NULL RETURNING id",
+        id,
+    )
+    .fetch_one(&mut *connection)
+    .await
+    .map_err(|error| match error {
+        sqlx::Error::RowNotFound => QueryError::RowNotFound(
+            "Active `admin` with requested id not found".to_string(),
+        ),
+        error => error.into(),
+    })?;
+
+    get_admin_info(id, connection).await
+}
+
+/// Makes sure an active `owner` not restricted to dirnames is left
+/// besides the `admin` with `id`, so `admin`s can still be managed.
+async fn check_other_owner(
+    id: i64,
+    action: &str,
+    connection: &mut SqliteConnection,
+) -> Result<(), QueryError> {
+    let others = sqlx::query_scalar!(
+        "SELECT COUNT(*) as \"count: i64\" FROM admin WHERE revoked_at IS \
+         NULL AND id != ? AND role = 'owner' AND NOT EXISTS (SELECT 1 FROM \
+         admin_dirname WHERE admin_id = admin.id)",
+        id,
+    )
+    .fetch_one(connection)
+    .await?;
+
+    match others {
+        0 => Err(QueryError::InvalidInput(format!(
+            "The last active unscoped `owner` can't be {action}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+async fn set_admin_dirnames(
+    id: i64,
+    dirnames: &[String],
+    connection: &mut SqliteConnection,
+) -> Result<(), QueryError> {
+    sqlx::query!("DELETE FROM admin_dirname WHERE admin_id = ?", id)
+        .execute(&mut *connection)
+        .await?;
+
+    for dirname in dirnames {
+        let dirname = dirname.trim_matches('/');
+        sqlx::query!(
+            "INSERT INTO admin_dirname (admin_id, dirname) VALUES (?, ?) ON \
+             CONFLICT DO NOTHING",
+            id,
+            dirname,
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_admin_dirnames(
+    id: i64,
+    connection: &mut SqliteConnection,
+) -> Result<Vec<String>, QueryError> {
+    sqlx::query_scalar!(
+        "SELECT dirname FROM admin_dirname WHERE admin_id = ? ORDER BY dirname",
+        id,
+    )
+    .fetch_all(connection)
+    .await
+    .map_err(|error| error.into())
+}
+
+async fn get_admin_info(
+    id: i64,
+    connection: &mut SqliteConnection,
+) -> Result<AdminInfo, QueryError> {
+    let admin = sqlx::query!(
+        "SELECT id, role, created_at as \"created_at!: DateTime<Utc>\", \
+         last_used_at as \"last_used_at: DateTime<Utc>\", revoked_at as \
+         \"revoked_at: DateTime<Utc>\" FROM admin WHERE id = ?",
+        id,
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+
+    Ok(AdminInfo {
+        id: admin.id,
+        role: admin.role.into(),
+        dirnames: get_admin_dirnames(id, connection).await?,
+        created_at: admin.created_at,
+        last_used_at: admin.last_used_at,
+        revoked_at: admin.revoked_at,
+    })
+}
+
+pub(crate) async fn list_admins(
+    connection: &mut SqliteConnection,
+) -> Result<Vec<AdminInfo>, QueryError> {
+    let ids = sqlx::query_scalar!("SELECT id FROM admin ORDER BY id")
+        .fetch_all(&mut *connection)
+        .await?;
+
+    let mut admins = Vec::new();
+    for id in ids {
+        admins.push(get_admin_info(id, &mut *connection).await?);
+    }
+
+    Ok(admins)
+}
+
+/// Hashes the plaintext keys left from before keys were hashed, returning
+/// how many were hashed.
+pub(crate) async fn hash_legacy_keys(
+    connection: &mut SqliteConnection,
+) -> Result<usize, QueryError> {
+    let legacy_admins =
+        sqlx::query!("SELECT id, key_hash FROM admin WHERE salt IS NULL")
+            .fetch_all(&mut *connection)
+            .await?;
+
+    for admin in &legacy_admins {
+        let salt = generate_secret();
+        let key_hash = hash_key(&salt, &admin.key_hash);
+
+        sqlx::query!(
+            "UPDATE admin SET key_hash = ?, salt = ? WHERE id = ?",
+            key_hash,
+            salt,
+            admin.id,
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    Ok(legacy_admins.len())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn hash_key_is_salted_sha256() {
+        let hash = super::hash_key(
+            "9c4e1b7a2f6d8e0c3a5b7d9f1e2c4a6b",
+            "ef8a53f0b0cb43dd764fe16a442752d6",
+        );
+
+        assert_eq!(
+            hash,
+            "508f690e7add847034c58c092d4e4c3c04dd9f0d612011cce5703a798b80ec0f"
+        );
+    }
+
+    #[test]
+    fn verify_key_accepts_only_matching_key() {
+        let salt = super::generate_secret();
+        let key = super::generate_secret();
+        let key_hash = super::hash_key(&salt, &key);
+
+        assert!(super::verify_key(&key, &salt, &key_hash));
+        assert!(!super::verify_key(
+            &super::generate_secret(),
+            &salt,
+            &key_hash
+        ));
+        assert!(!super::verify_key(&key, &super::generate_secret(), &key_hash));
+    }
+}
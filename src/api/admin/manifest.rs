@@ -0,0 +1,269 @@
+use std::path::Path;
+
+use rand::seq::SliceRandom;
+use rocket::http::RawStr;
+use serde::Deserialize;
+
+use super::pairing::PairingOptions;
+use crate::api::comparison::SelectionStrategy;
+
+/// File names a dirname's manifest can have, checked in this order.
+pub(crate) const MANIFEST_FILENAMES: [&str; 2] =
+    ["manifest.toml", "manifest.json"];
+
+/// Optional per-dirname settings for comparison generation, read from a
+/// `manifest.toml` or `manifest.json` file inside the dirname's folder.
+/// Replaces the `truncate_at_` dirname keyword, which still applies to
+/// folders without a manifest.
+#[derive(Deserialize)]
+#[serde(default)]
+pub(crate) struct Manifest {
+    #[serde(flatten)]
+    pub(crate) pairing: PairingOptions,
+    pub(crate) order: ImageOrder,
+    pub(crate) include_reversed: bool,
+    pub(crate) prompt: Option<String>,
+    pub(crate) selection_strategy: Option<SelectionStrategy>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            pairing: PairingOptions::default(),
+            order: ImageOrder::default(),
+            include_reversed: true,
+            prompt: None,
+            selection_strategy: None,
+        }
+    }
+}
+
+/// Order of the images given to the `PairGenerator`, either a keyword or
+/// an explicit list of file names; files missing from the list are
+/// appended by name.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum ImageOrder {
+    Keyword(OrderKeyword),
+    Explicit(Vec<String>),
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OrderKeyword {
+    Name,
+    NameDescending,
+    Random,
+}
+
+impl Default for ImageOrder {
+    fn default() -> Self {
+        Self::Keyword(OrderKeyword::Name)
+    }
+}
+
+/// Reads the manifest of the folder at `path`, if it has one.
+pub(crate) fn read_manifest(path: &Path) -> Result<Option<Manifest>, String> {
+    for filename in MANIFEST_FILENAMES {
+        let manifest_path = path.join(filename);
+        if !manifest_path.is_file() {
+            continue;
+        }
+
+        let content = std::fs::read_to_string(&manifest_path)
+            .map_err(|error| format!("{filename}: {}", error.kind()))?;
+
+        let manifest = if filename.ends_with(".toml") {
+            toml::from_str(&content).map_err(|error| error.to_string())
+        } else {
+            rocket::serde::json::from_str(&content)
+                .map_err(|error| error.to_string())
+        };
+
+        return manifest
+            .map(Some)
+            .map_err(|error| format!("{filename}: {error}"));
+    }
+
+    Ok(None)
+}
+
+impl ImageOrder {
+    /// Sorts `files`, the percent-encoded paths of the images of
+    /// `dirname`, in this order.
+    pub(crate) fn apply(
+        &self,
+        dirname: &str,
+        files: &mut [String],
+    ) -> Result<(), String> {
+        files.sort();
+
+        match self {
+            ImageOrder::Keyword(OrderKeyword::Name) => {},
+            ImageOrder::Keyword(OrderKeyword::NameDescending) => {
+                files.reverse()
+            },
+            ImageOrder::Keyword(OrderKeyword::Random) => {
+                files.shuffle(&mut rand::thread_rng())
+            },
+            ImageOrder::Explicit(filenames) => {
+                let paths = filenames
+                    .iter()
+                    .map(|filename| {
+                        let encoded = RawStr::new(filename).percent_encode();
+                        let path = format!("{dirname}/{encoded}")
+                            .trim_start_matches('/')
+                            .to_string();
+
+                        match files.contains(&path) {
+                            true => Ok(path),
+                            false => Err(format!(
+                                "`order` lists missing file `{filename}`"
+                            )),
+                        }
+                    })
+                    .collect::<Result<Vec<String>, String>>()?;
+
+                // the sort is stable, so unlisted files stay sorted by name
+                files.sort_by_key(|file| {
+                    paths
+                        .iter()
+                        .position(|path| path == file)
+                        .unwrap_or(paths.len())
+                });
+            },
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+    use rocket::fs::relative;
+
+    use super::{
+        ImageOrder,
+        OrderKeyword,
+    };
+    use crate::api::admin::pairing::Pairing;
+
+    fn files() -> Vec<String> {
+        vec![
+            "folder_a/image%202.png".to_string(),
+            "folder_a/image%203.png".to_string(),
+            "folder_a/image%201.png".to_string(),
+        ]
+    }
+
+    #[test]
+    fn read_manifest_parses_toml() {
+        let path = PathBuf::from(relative!("tests/static_dir/manifest"))
+            .join("reference_set");
+
+        let manifest = super::read_manifest(&path)
+            .expect("manifest to be valid")
+            .expect("manifest to be present");
+
+        assert!(matches!(manifest.pairing.pairing, Pairing::Reference));
+        assert!(!manifest.include_reversed);
+        assert_eq!(
+            manifest.order,
+            ImageOrder::Explicit(vec![
+                "image 3.png".to_string(),
+                "image 1.png".to_string(),
+            ])
+        );
+        assert_eq!(manifest.prompt.as_deref(), Some("Which image is sharper?"));
+    }
+
+    #[test]
+    fn read_manifest_parses_json_with_defaults() {
+        let path = PathBuf::from(relative!("tests/static_dir/manifest"))
+            .join("json_set");
+
+        let manifest = super::read_manifest(&path)
+            .expect("manifest to be valid")
+            .expect("manifest to be present");
+
+        assert!(matches!(manifest.pairing.pairing, Pairing::Windowed));
+        assert_eq!(manifest.pairing.truncate_at, Some(2));
+        assert!(manifest.include_reversed);
+        assert_eq!(
+            manifest.order,
+            ImageOrder::Keyword(OrderKeyword::NameDescending)
+        );
+        assert_eq!(manifest.prompt, None);
+    }
+
+    #[test]
+    fn read_manifest_returns_none_without_manifest() {
+        let path = PathBuf::from(relative!("tests/static_dir/ok"));
+
+        let manifest = super::read_manifest(&path).expect("no error");
+
+        assert!(manifest.is_none());
+    }
+
+    #[test]
+    fn read_manifest_returns_error_for_invalid_manifest() {
+        let path = PathBuf::from(relative!("tests/static_dir/manifest_error"))
+            .join("folder_a");
+
+        let error = super::read_manifest(&path).err();
+
+        assert!(error.is_some_and(|error| error.starts_with("manifest.toml")));
+    }
+
+    #[test]
+    fn order_by_name_descending() {
+        let mut files = files();
+
+        ImageOrder::Keyword(OrderKeyword::NameDescending)
+            .apply("folder_a", &mut files)
+            .expect("order to apply");
+
+        assert_eq!(
+            files,
+            vec![
+                "folder_a/image%203.png".to_string(),
+                "folder_a/image%202.png".to_string(),
+                "folder_a/image%201.png".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn explicit_order_appends_unlisted_files_by_name() {
+        let mut files = files();
+
+        ImageOrder::Explicit(vec!["image 3.png".to_string()])
+            .apply("folder_a", &mut files)
+            .expect("order to apply");
+
+        assert_eq!(
+            files,
+            vec![
+                "folder_a/image%203.png".to_string(),
+                "folder_a/image%201.png".to_string(),
+                "folder_a/image%202.png".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn explicit_order_with_missing_file_returns_error() {
+        let mut files = files();
+
+        let result = ImageOrder::Explicit(vec!["image 9.png".to_string()])
+            .apply("folder_a", &mut files);
+
+        assert_eq!(
+            result,
+            Err("`order` lists missing file `image 9.png`".to_string())
+        );
+    }
+}
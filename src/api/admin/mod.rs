@@ -1,46 +1,376 @@
 pub(crate) mod handler;
+pub(crate) mod key;
+pub(crate) mod manifest;
+pub(crate) mod pairing;
 
 use std::{
-    collections::BTreeMap,
-    path::PathBuf,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+        HashSet,
+    },
+    fmt::Display,
+    ops::Deref,
+    path::{
+        Path,
+        PathBuf,
+    },
 };
 
 use rocket::{
-    http::RawStr,
+    form::{
+        self,
+        error::ErrorKind,
+    },
+    http::{
+        RawStr,
+        Status,
+    },
     State,
 };
-use sqlx::SqliteConnection;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sqlx::{
+    Connection,
+    SqliteConnection,
+};
 use uuid::Uuid;
 
+use self::{
+    manifest::{
+        Manifest,
+        MANIFEST_FILENAMES,
+    },
+    pairing::{
+        PairGenerator,
+        PairingOptions,
+    },
+};
 use super::{
-    comparison::Comparison,
+    comparison::{
+        Comparison,
+        SelectionStrategy,
+    },
+    project::ProjectChange,
     QueryError,
+    SqliteArray,
+};
+use crate::{
+    StaticDir,
+    STATIC_ROUTE,
 };
-use crate::StaticDir;
 
+/// Finds the non-revoked `admin` with `key`, checking it against every
+/// stored hash so the time taken doesn't depend on which one matches,
+/// and records its use.
 pub(crate) async fn get_admin(
     key: &str,
     connection: &mut SqliteConnection,
 ) -> Result<Admin, QueryError> {
-    sqlx::query_as!(Admin, "SELECT id FROM admin WHERE key = ?", key)
-        .fetch_one(connection)
-        .await
-        .map_err(|error| error.into())
+    let admins = sqlx::query!(
+        "SELECT id, key_hash, salt as \"salt!\", role FROM admin WHERE \
+         revoked_at IS NULL AND salt IS NOT NULL"
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    let mut found = None;
+    for admin in admins {
+        if key::verify_key(key, &admin.salt, &admin.key_hash) {
+            found = Some((admin.id, admin.role));
+        }
+    }
+
+    let (id, role) = found.ok_or(QueryError::RowNotFound(
+        "`admin` with requested key not found".to_string(),
+    ))?;
+
+    sqlx::query!(
+        "UPDATE admin SET last_used_at = datetime('now') WHERE id = ?",
+        id
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    let dirnames = key::get_admin_dirnames(id, connection).await?;
+
+    Ok(Admin {
+        id,
+        role: role.into(),
+        dirnames,
+    })
 }
 
 pub(crate) struct Admin {
     pub(crate) id: i64,
+    pub(crate) role: AdminRole,
+    /// Dirnames the `admin` is restricted to, along with their
+    /// subfolders; empty when it can access every dirname.
+    pub(crate) dirnames: Vec<String>,
 }
 
-pub(crate) async fn generate_comparisons_from_static_dir<'r>(
+impl Admin {
+    pub(crate) fn can_access(&self, dirname: &str) -> bool {
+        self.dirnames.is_empty()
+            || self.dirnames.iter().any(|scope| {
+                dirname == scope
+                    || dirname
+                        .strip_prefix(scope.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+    }
+
+    pub(crate) fn check_access(&self, dirname: &str) -> Result<(), QueryError> {
+        match self.can_access(dirname) {
+            true => Ok(()),
+            false => Err(QueryError::Forbidden(format!(
+                "`admin` can't access dirname `{dirname}`"
+            ))),
+        }
+    }
+
+    /// Only `owner`s not restricted to dirnames can manage `admin`s, so
+    /// no `admin` can grant access it doesn't have, or read the audit log
+    /// spanning every dirname.
+    pub(crate) fn check_unscoped_owner(&self) -> Result<(), QueryError> {
+        match self.role == AdminRole::Owner && self.dirnames.is_empty() {
+            true => Ok(()),
+            false => Err(QueryError::Forbidden(
+                "Only `owner`s not restricted to dirnames can do this"
+                    .to_string(),
+            )),
+        }
+    }
+}
+
+/// What an `admin` is allowed to do, each role including the ones before
+/// it.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AdminRole {
+    /// Reads rankings, `comparison`s, `project`s and exports.
+    Viewer,
+    /// Also generates and deletes `comparison`s.
+    Curator,
+    /// Also sets `project`s, deletes whole dirnames and manages `admin`s.
+    Owner,
+}
+
+impl From<String> for AdminRole {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "owner" => AdminRole::Owner,
+            "curator" => AdminRole::Curator,
+            _ => AdminRole::Viewer,
+        }
+    }
+}
+
+impl Display for AdminRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            AdminRole::Viewer => "viewer",
+            AdminRole::Curator => "curator",
+            AdminRole::Owner => "owner",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// An `Admin` with at least the `viewer` role.
+pub(crate) struct Viewer(pub(crate) Admin);
+
+/// An `Admin` with at least the `curator` role.
+pub(crate) struct Curator(pub(crate) Admin);
+
+/// An `Admin` with the `owner` role.
+pub(crate) struct Owner(pub(crate) Admin);
+
+impl Deref for Viewer {
+    type Target = Admin;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Deref for Curator {
+    type Target = Admin;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Deref for Owner {
+    type Target = Admin;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// How `generate_comparisons_from_static_dir` treats the `comparison`s
+/// already in the database.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct GenerationOptions {
+    /// Only create the pairs involving images not yet in any active
+    /// `comparison` of their dirname.
+    pub(crate) incremental: bool,
+    /// Retire the active `comparison`s showing images no longer in
+    /// `STATIC_DIR`, so they are not handed out to users anymore.
+    pub(crate) retire_missing: bool,
+    pub(crate) transaction: TransactionScope,
+}
+
+/// Whether a generation run is written in a single transaction, so any
+/// error leaves the database untouched, or in one transaction per
+/// dirname, so the dirnames without errors are still generated.
+#[derive(Clone, Copy, Default, PartialEq, FromFormField)]
+pub(crate) enum TransactionScope {
+    #[default]
+    #[field(value = "all")]
+    All,
+    #[field(value = "dirname")]
+    Dirname,
+}
+
+impl TransactionScope {
+    /// Takes the scope from the `transaction` query parameter, defaulting
+    /// to `all` when it is missing.
+    pub(crate) fn from_query(
+        value: form::Result<'_, Self>,
+    ) -> Result<Self, QueryError> {
+        match value {
+            Ok(scope) => Ok(scope),
+            Err(errors)
+                if errors
+                    .iter()
+                    .all(|error| matches!(error.kind, ErrorKind::Missing)) =>
+            {
+                Ok(Self::default())
+            },
+            Err(_) => Err(QueryError::InvalidInput(
+                "`transaction` must be `all` or `dirname`".to_string(),
+            )),
+        }
+    }
+}
+
+/// Why a dirname could not be generated.
+#[derive(Serialize)]
+pub(crate) struct DirnameError {
+    pub(crate) dirname: String,
+    pub(crate) error: String,
+}
+
+/// Error of a generation run: either one that prevented planning it, or
+/// the errors of the dirnames that could not be generated, in which case
+/// nothing was written.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum GenerationError {
+    Query(QueryError),
+    Dirnames(Vec<DirnameError>),
+}
+
+impl From<QueryError> for GenerationError {
+    fn from(error: QueryError) -> Self {
+        Self::Query(error)
+    }
+}
+
+impl GenerationError {
+    pub(crate) fn default_status(&self) -> Status {
+        match self {
+            Self::Query(error) => error.default_status(),
+            Self::Dirnames(_) => Status::InternalServerError,
+        }
+    }
+}
+
+/// Outcome of a generation run: the `comparison`s created (or, when not
+/// incremental, regenerated), the active ones left untouched and the
+/// ones retired.
+#[derive(Default, Serialize)]
+pub(crate) struct GenerationReport<'r> {
+    pub(crate) created: Vec<Comparison<'r>>,
+    pub(crate) existing: Vec<Comparison<'r>>,
+    pub(crate) retired: Vec<Comparison<'r>>,
+    /// Dirnames left untouched because of an error, when generating
+    /// with one transaction per dirname.
+    pub(crate) failed: Vec<DirnameError>,
+}
+
+impl<'r> GenerationReport<'r> {
+    fn extend(&mut self, other: GenerationReport<'r>) {
+        self.created.extend(other.created);
+        self.existing.extend(other.existing);
+        self.retired.extend(other.retired);
+        self.failed.extend(other.failed);
+    }
+}
+
+/// What generating comparisons does to one dirname, planned without
+/// writing to the database. A dirname with an `error` can't be generated.
+pub(crate) struct DirnamePlan<'r> {
+    pub(crate) dirname: String,
+    pub(crate) images: usize,
+    /// Images of the `comparison`s to create, joined by `///`.
+    pub(crate) pairs: Vec<String>,
+    pub(crate) existing: Vec<Comparison<'r>>,
+    pub(crate) retired: Vec<Comparison<'r>>,
+    pub(crate) prompt: Option<String>,
+    pub(crate) selection_strategy: Option<SelectionStrategy>,
+    pub(crate) error: Option<String>,
+}
+
+/// Summary of a `DirnamePlan`, returned by dry runs.
+#[derive(Serialize)]
+pub(crate) struct DirnamePreview<'r> {
+    pub(crate) dirname: String,
+    pub(crate) images: usize,
+    pub(crate) created: usize,
+    pub(crate) existing: usize,
+    pub(crate) retired: usize,
+    pub(crate) pairs: Vec<SqliteArray<'r>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+impl<'r> From<DirnamePlan<'_>> for DirnamePreview<'r> {
+    fn from(plan: DirnamePlan) -> Self {
+        Self {
+            dirname: plan.dirname,
+            images: plan.images,
+            created: plan.pairs.len(),
+            existing: plan.existing.len(),
+            retired: plan.retired.len(),
+            pairs: plan.pairs.into_iter().map(SqliteArray::from).collect(),
+            error: plan.error,
+        }
+    }
+}
+
+/// Plans the generation of comparisons for every dirname in
+/// `STATIC_DIR` or with active `comparison`s that `admin` can access,
+/// without writing anything.
+pub(crate) async fn plan_comparisons_from_static_dir<'r>(
     admin: &Admin,
+    pairing: &PairingOptions,
+    options: &GenerationOptions,
     static_dir: &State<StaticDir>,
     connection: &mut SqliteConnection,
-) -> Result<Vec<Comparison<'r>>, QueryError> {
+) -> Result<Vec<DirnamePlan<'r>>, QueryError> {
     let static_dir_files = read_dir_files(&static_dir.path, "".to_string())
         .map_err(|error| {
             QueryError::FileServerError(error.kind().to_string())
         })?;
+    let on_disk: HashSet<String> = static_dir_files.iter().cloned().collect();
 
     let mut files_by_dirname: BTreeMap<String, Vec<String>> = BTreeMap::new();
     for path in static_dir_files.into_iter() {
@@ -54,62 +384,393 @@ pub(crate) async fn generate_comparisons_from_static_dir<'r>(
         (*entry).push(path);
     }
 
-    let mut comparisons = Vec::new();
-    for (dirname, files) in &mut files_by_dirname {
-        if files.len() < 2 {
-            return Err(QueryError::FileServerError(format!(
-                "Not enough files in STATIC_DIR/{dirname} (minimum 2 needed)"
-            )));
+    let mut active_by_dirname: BTreeMap<String, Vec<Comparison>> =
+        BTreeMap::new();
+    for comparison in get_active_comparisons(connection).await? {
+        active_by_dirname
+            .entry(comparison.dirname.clone())
+            .or_default()
+            .push(comparison);
+    }
+
+    let dirnames: BTreeSet<String> = files_by_dirname
+        .keys()
+        .chain(active_by_dirname.keys())
+        .filter(|dirname| admin.can_access(dirname))
+        .cloned()
+        .collect();
+
+    let mut plans = Vec::new();
+    for dirname in dirnames {
+        let mut files = files_by_dirname.remove(&dirname).unwrap_or_default();
+        let active = active_by_dirname.remove(&dirname).unwrap_or_default();
+
+        let (retired, existing): (Vec<Comparison>, Vec<Comparison>) =
+            active.into_iter().partition(|comparison| {
+                options.retire_missing
+                    && comparison_files(comparison)
+                        .iter()
+                        .any(|file| !on_disk.contains(file))
+            });
+
+        let mut plan = DirnamePlan {
+            images: files.len(),
+            dirname,
+            pairs: Vec::new(),
+            existing,
+            retired,
+            prompt: None,
+            selection_strategy: None,
+            error: None,
+        };
+
+        // dirnames left only in the database have nothing to generate
+        if !files.is_empty() {
+            if let Err(error) = plan_pairs(
+                &mut plan,
+                &mut files,
+                pairing,
+                options,
+                &static_dir.path,
+            ) {
+                plan.error = Some(error);
+            }
         }
 
-        let truncate_at = get_truncate_at_from_dirname(dirname);
-
-        // assumes the file names are such that
-        // default sorting will arrange them
-        // by "distance", so "file 1" and "file 2" are
-        // more similar and "file 1" and "file 6" are more
-        // dissimilar, comparatevely
-        files.sort();
-
-        for pair in generate_pairs(files, truncate_at) {
-            let comparison = create_comparison(
-                &format!("{}///{}", pair.0, pair.1),
-                dirname,
-                admin,
-                connection,
-            )
-            .await?;
-            comparisons.push(comparison);
+        plans.push(plan);
+    }
+
+    Ok(plans)
+}
+
+/// Fills in the pairs to create for `plan` from the `files` of its
+/// dirname, along with the `project` settings of its manifest.
+fn plan_pairs(
+    plan: &mut DirnamePlan,
+    files: &mut [String],
+    pairing: &PairingOptions,
+    options: &GenerationOptions,
+    static_dir: &Path,
+) -> Result<(), String> {
+    let dirname = plan.dirname.as_str();
+    if files.len() < 2 {
+        return Err(format!(
+            "Not enough files in STATIC_DIR/{dirname} (minimum 2 needed)"
+        ));
+    }
+
+    let manifest_path =
+        static_dir.join(RawStr::new(dirname).percent_decode_lossy().as_ref());
+    let manifest =
+        manifest::read_manifest(&manifest_path).map_err(|error| {
+            format!("Invalid manifest in STATIC_DIR/{dirname}: {error}")
+        })?;
+
+    // a manifest replaces the pairing options of the request, which
+    // fall back to the `truncate_at_` dirname keyword
+    let manifest_pairing;
+    let (pairing, include_reversed) = match manifest {
+        Some(Manifest {
+            pairing,
+            order,
+            include_reversed,
+            prompt,
+            selection_strategy,
+        }) => {
+            order.apply(dirname, files).map_err(|error| {
+                format!("Invalid manifest in STATIC_DIR/{dirname}: {error}")
+            })?;
+            plan.prompt = prompt;
+            plan.selection_strategy = selection_strategy;
+            manifest_pairing = pairing;
+
+            (&manifest_pairing, include_reversed)
+        },
+        None => {
+            // assumes the file names are such that
+            // default sorting will arrange them
+            // by "distance", so "file 1" and "file 2" are
+            // more similar and "file 1" and "file 6" are more
+            // dissimilar, comparatevely
+            files.sort();
+
+            (pairing, true)
+        },
+    };
+
+    let tuple_size = pairing.tuple_size().map_err(|error| {
+        format!("Invalid pairing for STATIC_DIR/{dirname}: {error}")
+    })?;
+    if files.len() < tuple_size {
+        return Err(format!(
+            "Not enough files in STATIC_DIR/{dirname} (minimum {tuple_size} \
+             needed)"
+        ));
+    }
+
+    let mut known_images: HashSet<String> = HashSet::new();
+    let mut known_pairs: HashSet<String> = HashSet::new();
+    for comparison in &plan.existing {
+        let files = comparison_files(comparison);
+        known_pairs.insert(files.join("///"));
+        known_images.extend(files);
+    }
+
+    let mut tuples = match tuple_size {
+        2 => generate_pairs(
+            pairing.pair_generator(dirname).as_ref(),
+            files,
+            include_reversed,
+        )
+        .into_iter()
+        .map(|(a, b)| vec![a, b])
+        .collect(),
+        size => generate_tuples(files, size, include_reversed),
+    };
+    if pairing.ratings {
+        tuples.extend(files.iter().map(|file| vec![file.clone()]));
+    }
+
+    for tuple in tuples {
+        let images = tuple.join("///");
+        if options.incremental
+            && (known_pairs.contains(&images)
+                || tuple.iter().all(|image| known_images.contains(image)))
+        {
+            continue;
         }
+
+        plan.pairs.push(images);
     }
 
-    Ok(comparisons)
+    // every active `comparison` generated again is upserted, so it is
+    // reported as created
+    let pairs: HashSet<&String> = plan.pairs.iter().collect();
+    plan.existing.retain(|comparison| {
+        !pairs.contains(&comparison_files(comparison).join("///"))
+    });
+
+    Ok(())
 }
 
-fn get_truncate_at_from_dirname(dirname: &String) -> Option<usize> {
+pub(crate) async fn generate_comparisons_from_static_dir<'r>(
+    admin: &Admin,
+    pairing: &PairingOptions,
+    options: &GenerationOptions,
+    static_dir: &State<StaticDir>,
+    connection: &mut SqliteConnection,
+) -> Result<GenerationReport<'r>, GenerationError> {
+    let plans = plan_comparisons_from_static_dir(
+        admin,
+        pairing,
+        options,
+        static_dir,
+        &mut *connection,
+    )
+    .await?;
+
+    let mut report = GenerationReport::default();
+    let (plans, failed): (Vec<DirnamePlan>, Vec<DirnamePlan>) =
+        plans.into_iter().partition(|plan| plan.error.is_none());
+    let errors = failed.into_iter().map(|plan| DirnameError {
+        error: plan.error.unwrap_or_default(),
+        dirname: plan.dirname,
+    });
+
+    match options.transaction {
+        TransactionScope::All => {
+            let errors: Vec<DirnameError> = errors.collect();
+            if !errors.is_empty() {
+                return Err(GenerationError::Dirnames(errors));
+            }
+
+            let mut transaction =
+                connection.begin().await.map_err(QueryError::from)?;
+            for plan in plans {
+                let dirname = plan.dirname.clone();
+                match apply_plan(plan, admin, &mut transaction).await {
+                    Ok(dirname_report) => report.extend(dirname_report),
+                    Err(error) => {
+                        // dropping the transaction rolls it back
+                        return Err(GenerationError::Dirnames(vec![
+                            DirnameError {
+                                dirname,
+                                error: error.to_string(),
+                            },
+                        ]));
+                    },
+                }
+            }
+            transaction.commit().await.map_err(QueryError::from)?;
+        },
+        TransactionScope::Dirname => {
+            report.failed.extend(errors);
+
+            for plan in plans {
+                let dirname = plan.dirname.clone();
+                let mut transaction =
+                    connection.begin().await.map_err(QueryError::from)?;
+                match apply_plan(plan, admin, &mut transaction).await {
+                    Ok(dirname_report) => {
+                        transaction.commit().await.map_err(QueryError::from)?;
+                        report.extend(dirname_report);
+                    },
+                    Err(error) => report.failed.push(DirnameError {
+                        dirname,
+                        error: error.to_string(),
+                    }),
+                }
+            }
+        },
+    }
+
+    Ok(report)
+}
+
+/// Writes what `plan` planned for its dirname.
+async fn apply_plan<'r>(
+    plan: DirnamePlan<'r>,
+    admin: &Admin,
+    connection: &mut SqliteConnection,
+) -> Result<GenerationReport<'r>, QueryError> {
+    let mut report = GenerationReport::default();
+
+    for comparison in plan.retired {
+        retire_comparison(&comparison, &mut *connection).await?;
+        report.retired.push(comparison);
+    }
+
+    set_project_from_manifest(
+        &plan.dirname,
+        plan.prompt,
+        plan.selection_strategy,
+        &mut *connection,
+    )
+    .await?;
+
+    for images in &plan.pairs {
+        let comparison =
+            create_comparison(images, &plan.dirname, admin, &mut *connection)
+                .await?;
+        report.created.push(comparison);
+    }
+
+    report.existing = plan.existing;
+
+    Ok(report)
+}
+
+/// Returns the paths of the images of `comparison`, relative to
+/// `STATIC_DIR` and percent-encoded, as returned by `read_dir_files`.
+fn comparison_files(comparison: &Comparison) -> Vec<String> {
+    comparison
+        .images
+        .iter()
+        .map(|image| {
+            image
+                .path()
+                .as_str()
+                .trim_start_matches(STATIC_ROUTE)
+                .trim_start_matches('/')
+                .to_string()
+        })
+        .collect()
+}
+
+async fn get_active_comparisons<'r>(
+    connection: &mut SqliteConnection,
+) -> Result<Vec<Comparison<'r>>, QueryError> {
+    sqlx::query_as!(
+        Comparison,
+        "SELECT id, dirname, images, created_at as \"created_at: _\", \
+         created_by FROM comparison WHERE retired_at IS NULL ORDER BY \
+         dirname, created_at",
+    )
+    .fetch_all(connection)
+    .await
+    .map_err(|error| error.into())
+}
+
+async fn retire_comparison(
+    comparison: &Comparison<'_>,
+    connection: &mut SqliteConnection,
+) -> Result<(), QueryError> {
+    sqlx::query!(
+        "UPDATE comparison SET retired_at = datetime('now') WHERE id = ?",
+        *comparison.id,
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
+
+/// Saves the `project` settings given in the manifest of `dirname`,
+/// keeping the current ones for settings it leaves out.
+async fn set_project_from_manifest(
+    dirname: &str,
+    prompt: Option<String>,
+    selection_strategy: Option<SelectionStrategy>,
+    connection: &mut SqliteConnection,
+) -> Result<(), QueryError> {
+    if prompt.is_none() && selection_strategy.is_none() {
+        return Ok(());
+    }
+
+    let change = ProjectChange {
+        selection_strategy,
+        prompt: prompt.map(Some),
+        ..Default::default()
+    };
+    super::project::set_project(dirname, change, connection).await?;
+
+    Ok(())
+}
+
+fn get_truncate_at_from_dirname(dirname: &str) -> Option<usize> {
     let keyword = "truncate_at_";
     let index = dirname.find(keyword)?;
     let (_, truncate_at) = dirname.split_at(index + keyword.len());
     truncate_at.parse().ok()
 }
 
-fn generate_pairs<T: Clone>(
-    list: &Vec<T>,
-    truncate_at: Option<usize>,
-) -> Vec<(T, T)> {
-    let mut pairs = Vec::new();
-    let truncate_at = truncate_at.unwrap_or(list.len());
-
-    for a in 0..(list.len() - 1) {
-        // this truncates the comparisons so we only generate
-        // comparisons for "near" elements in the list
-        for b in (a + 1)..(a + truncate_at).min(list.len()) {
-            pairs.push((list[a].clone(), list[b].clone()));
-            pairs.push((list[b].clone(), list[a].clone()));
-        }
+/// Generates the pairs to compare with `pair_generator`, each also in
+/// reverse order if `include_reversed`, so every image is shown on
+/// either side.
+fn generate_pairs(
+    pair_generator: &dyn PairGenerator,
+    list: &[String],
+    include_reversed: bool,
+) -> Vec<(String, String)> {
+    let pairs = pair_generator.generate(list);
+    if !include_reversed {
+        return pairs;
     }
 
     pairs
+        .into_iter()
+        .flat_map(|(a, b)| [(a.clone(), b.clone()), (b, a)])
+        .collect()
+}
+
+/// Generates a tuple from every `size` consecutive images of `list`, each
+/// also rotated if `include_reversed`, so every image is shown in every
+/// position.
+fn generate_tuples(
+    list: &[String],
+    size: usize,
+    include_reversed: bool,
+) -> Vec<Vec<String>> {
+    list.windows(size)
+        .flat_map(|window| {
+            let rotations = if include_reversed { size } else { 1 };
+            (0..rotations).map(move |rotation| {
+                let mut tuple = window.to_vec();
+                tuple.rotate_left(rotation);
+                tuple
+            })
+        })
+        .collect()
 }
 
 async fn create_comparison<'r>(
@@ -123,7 +784,7 @@ async fn create_comparison<'r>(
     sqlx::query_as!(
         Comparison,
         "INSERT INTO comparison (id, dirname, images, created_by) VALUES (?, \
-         ?, ?, ?) ON CONFLICT DO UPDATE SET images=images RETURNING id, \
+         ?, ?, ?) ON CONFLICT DO UPDATE SET retired_at = NULL RETURNING id, \
          dirname, images, created_at as \"created_at: _\", created_by",
         id,
         dirname,
@@ -177,6 +838,10 @@ fn read_dir_files(
             })?;
 
         if metadata.is_file() {
+            if MANIFEST_FILENAMES.contains(&filename.as_str()) {
+                continue;
+            }
+
             files.push(
                 format!("{dirname}/{filename}")
                     .trim_start_matches('/')
@@ -260,7 +925,11 @@ mod test {
         // permutations = n! / (n-r)!
         let expected_permutations_len = 462;
 
-        let test_permutations = super::generate_pairs(&test_list, None);
+        let test_permutations = super::generate_pairs(
+            &super::pairing::Windowed { truncate_at: None },
+            &test_list,
+            true,
+        );
 
         assert_eq!(test_permutations.len(), expected_permutations_len);
     }
@@ -313,8 +982,13 @@ mod test {
             ("image%208.png".to_string(), "image%207.png".to_string()),
         ];
 
-        let test_permutations_truncated =
-            super::generate_pairs(&test_list, Some(3));
+        let test_permutations_truncated = super::generate_pairs(
+            &super::pairing::Windowed {
+                truncate_at: Some(3),
+            },
+            &test_list,
+            true,
+        );
 
         assert_eq!(
             test_permutations_truncated,
@@ -322,6 +996,37 @@ mod test {
         );
     }
 
+    #[test]
+    fn generate_tuples_rotates_consecutive_images() {
+        let test_list = vec![
+            "image%201.png".to_string(),
+            "image%202.png".to_string(),
+            "image%203.png".to_string(),
+            "image%204.png".to_string(),
+        ];
+
+        let tuples = super::generate_tuples(&test_list, 3, true);
+
+        assert_eq!(tuples.len(), 2 * 3);
+        assert_eq!(tuples[0], test_list[0..3].to_vec());
+        assert_eq!(
+            tuples[1],
+            vec![
+                "image%202.png".to_string(),
+                "image%203.png".to_string(),
+                "image%201.png".to_string(),
+            ]
+        );
+        assert_eq!(tuples[3], test_list[1..4].to_vec());
+
+        let tuples = super::generate_tuples(&test_list, 3, false);
+
+        assert_eq!(
+            tuples,
+            vec![test_list[0..3].to_vec(), test_list[1..4].to_vec(),]
+        );
+    }
+
     #[test]
     fn get_truncate_at_from_dirname() {
         let tests = vec![
@@ -337,4 +1042,18 @@ mod test {
             assert_eq!(truncate_at, expected_truncate_at);
         }
     }
+
+    #[test]
+    fn can_access_scoped_dirnames_and_subfolders() {
+        let admin = super::Admin {
+            id: 1,
+            role: super::AdminRole::Viewer,
+            dirnames: vec!["folder_b".to_string()],
+        };
+
+        assert!(admin.can_access("folder_b"));
+        assert!(admin.can_access("folder_b/folder_c"));
+        assert!(!admin.can_access("folder_b_2"));
+        assert!(!admin.can_access(""));
+    }
 }
@@ -0,0 +1,333 @@
+use std::collections::BTreeSet;
+
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+const DEFAULT_DEGREE: usize = 4;
+const DEFAULT_ROUNDS: usize = 3;
+
+/// Design used to decide which images of a dirname are compared.
+pub(crate) trait PairGenerator {
+    /// Returns the unordered pairs of images to compare, given the images
+    /// of a dirname in comparison order.
+    fn generate(&self, list: &[String]) -> Vec<(String, String)>;
+}
+
+/// Compares every image with the next `truncate_at - 1` images in the
+/// list, or with every other image if not truncated.
+pub(crate) struct Windowed {
+    pub(crate) truncate_at: Option<usize>,
+}
+
+impl PairGenerator for Windowed {
+    fn generate(&self, list: &[String]) -> Vec<(String, String)> {
+        let mut pairs = Vec::new();
+        let truncate_at = self.truncate_at.unwrap_or(list.len());
+
+        for a in 0..list.len().saturating_sub(1) {
+            // this truncates the comparisons so we only generate
+            // comparisons for "near" elements in the list
+            for b in (a + 1)..(a + truncate_at).min(list.len()) {
+                pairs.push((list[a].clone(), list[b].clone()));
+            }
+        }
+
+        pairs
+    }
+}
+
+/// Compares every image with every other image.
+pub(crate) struct RoundRobin;
+
+impl PairGenerator for RoundRobin {
+    fn generate(&self, list: &[String]) -> Vec<(String, String)> {
+        Windowed { truncate_at: None }.generate(list)
+    }
+}
+
+/// Compares every image with about `degree` random others. The images
+/// are first joined in a random cycle, so the comparison graph is always
+/// connected, then random edges are added between images still below
+/// the target degree, lowest degree first.
+pub(crate) struct RandomSparse {
+    pub(crate) degree: usize,
+}
+
+impl PairGenerator for RandomSparse {
+    fn generate(&self, list: &[String]) -> Vec<(String, String)> {
+        if self.degree + 1 >= list.len() {
+            return RoundRobin.generate(list);
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut order: Vec<usize> = (0..list.len()).collect();
+        order.shuffle(&mut rng);
+
+        let mut edges: BTreeSet<(usize, usize)> = BTreeSet::new();
+        let mut degrees = vec![0; list.len()];
+
+        for (i, &a) in order.iter().enumerate() {
+            let b = order[(i + 1) % order.len()];
+            connect(&mut edges, &mut degrees, a, b);
+        }
+
+        loop {
+            let mut open: Vec<usize> = (0..list.len())
+                .filter(|&node| degrees[node] < self.degree)
+                .collect();
+            open.shuffle(&mut rng);
+            open.sort_by_key(|&node| degrees[node]);
+
+            let candidate = open.iter().enumerate().find_map(|(i, &a)| {
+                open[(i + 1)..]
+                    .iter()
+                    .find(|&&b| !edges.contains(&(a.min(b), a.max(b))))
+                    .map(|&b| (a, b))
+            });
+
+            match candidate {
+                Some((a, b)) => connect(&mut edges, &mut degrees, a, b),
+                None => break,
+            }
+        }
+
+        edges
+            .into_iter()
+            .map(|(a, b)| (list[a].clone(), list[b].clone()))
+            .collect()
+    }
+}
+
+fn connect(
+    edges: &mut BTreeSet<(usize, usize)>,
+    degrees: &mut [usize],
+    a: usize,
+    b: usize,
+) {
+    if edges.insert((a.min(b), a.max(b))) {
+        degrees[a] += 1;
+        degrees[b] += 1;
+    }
+}
+
+/// Swiss-tournament style pairing over `rounds` rounds, using the list
+/// order as the standings: in each round every image is paired with the
+/// next unpaired image below it that it has not met yet. With an odd
+/// number of images, one of them sits out each round, starting from the
+/// bottom of the standings.
+pub(crate) struct Swiss {
+    pub(crate) rounds: usize,
+}
+
+impl PairGenerator for Swiss {
+    fn generate(&self, list: &[String]) -> Vec<(String, String)> {
+        let mut met: BTreeSet<(usize, usize)> = BTreeSet::new();
+        let mut pairs = Vec::new();
+
+        for round in 0..self.rounds {
+            let mut paired = vec![false; list.len()];
+            if list.len() % 2 == 1 {
+                paired[(list.len() - 1) - (round % list.len())] = true;
+            }
+
+            for a in 0..list.len() {
+                if paired[a] {
+                    continue;
+                }
+
+                let opponent = ((a + 1)..list.len())
+                    .find(|&b| !paired[b] && !met.contains(&(a, b)));
+
+                if let Some(b) = opponent {
+                    paired[a] = true;
+                    paired[b] = true;
+                    met.insert((a, b));
+                    pairs.push((list[a].clone(), list[b].clone()));
+                }
+            }
+        }
+
+        pairs
+    }
+}
+
+/// Compares every image with the first image of the list.
+pub(crate) struct Reference;
+
+impl PairGenerator for Reference {
+    fn generate(&self, list: &[String]) -> Vec<(String, String)> {
+        match list.split_first() {
+            None => Vec::new(),
+            Some((reference, rest)) => rest
+                .iter()
+                .map(|image| (reference.clone(), image.clone()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, FromFormField, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Pairing {
+    #[default]
+    #[field(value = "windowed")]
+    Windowed,
+    #[field(value = "round_robin")]
+    RoundRobin,
+    #[field(value = "random")]
+    Random,
+    #[field(value = "swiss")]
+    Swiss,
+    #[field(value = "reference")]
+    Reference,
+}
+
+/// Choice of `PairGenerator` and its parameters, taken from the query of
+/// `POST /api/admin/comparison`. Without a `pairing` the windowed scheme
+/// is used, truncated at `truncate_at` or else at the value parsed from
+/// the dirname. A `tuple_size` above 2 groups that many consecutive
+/// images per `comparison` instead, which only the windowed scheme does.
+/// With `ratings`, every image also gets a `comparison` of its own, to be
+/// rated on the scale of the `project`.
+#[derive(Default, FromForm, Deserialize)]
+#[serde(default)]
+pub(crate) struct PairingOptions {
+    #[field(default_with = Some(Pairing::Windowed))]
+    pub(crate) pairing: Pairing,
+    pub(crate) truncate_at: Option<usize>,
+    pub(crate) degree: Option<usize>,
+    pub(crate) rounds: Option<usize>,
+    pub(crate) tuple_size: Option<usize>,
+    pub(crate) ratings: bool,
+}
+
+impl PairingOptions {
+    /// Number of images in each `comparison`, 2 unless set otherwise.
+    pub(crate) fn tuple_size(&self) -> Result<usize, String> {
+        match self.tuple_size.unwrap_or(2) {
+            size if size < 2 => {
+                Err("`tuple_size` must be at least 2".to_string())
+            },
+            size if size > 2 && self.pairing != Pairing::Windowed => {
+                Err("`tuple_size` above 2 needs the `windowed` pairing"
+                    .to_string())
+            },
+            size => Ok(size),
+        }
+    }
+
+    pub(crate) fn pair_generator(
+        &self,
+        dirname: &str,
+    ) -> Box<dyn PairGenerator + Send + Sync> {
+        match self.pairing {
+            Pairing::Windowed => Box::new(Windowed {
+                truncate_at: self
+                    .truncate_at
+                    .or_else(|| super::get_truncate_at_from_dirname(dirname)),
+            }),
+            Pairing::RoundRobin => Box::new(RoundRobin),
+            Pairing::Random => Box::new(RandomSparse {
+                degree: self.degree.unwrap_or(DEFAULT_DEGREE),
+            }),
+            Pairing::Swiss => Box::new(Swiss {
+                rounds: self.rounds.unwrap_or(DEFAULT_ROUNDS),
+            }),
+            Pairing::Reference => Box::new(Reference),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use pretty_assertions::assert_eq;
+
+    use super::PairGenerator;
+
+    fn images(len: usize) -> Vec<String> {
+        (1..=len).map(|i| format!("image%20{i}.png")).collect()
+    }
+
+    fn degrees(pairs: &[(String, String)]) -> BTreeMap<String, usize> {
+        let mut degrees = BTreeMap::new();
+        for (a, b) in pairs {
+            *degrees.entry(a.clone()).or_default() += 1;
+            *degrees.entry(b.clone()).or_default() += 1;
+        }
+        degrees
+    }
+
+    #[test]
+    fn round_robin_returns_all_combinations() {
+        let pairs = super::RoundRobin.generate(&images(7));
+
+        // combinations = n! / (r! * (n-r)!)
+        assert_eq!(pairs.len(), 21);
+    }
+
+    #[test]
+    fn random_sparse_reaches_target_degree() {
+        let list = images(20);
+
+        let pairs = super::RandomSparse { degree: 4 }.generate(&list);
+        let degrees = degrees(&pairs);
+
+        assert_eq!(degrees.len(), list.len());
+        for degree in degrees.values() {
+            assert!((3..=4).contains(degree));
+        }
+    }
+
+    #[test]
+    fn random_sparse_with_high_degree_returns_all_combinations() {
+        let pairs = super::RandomSparse { degree: 10 }.generate(&images(5));
+
+        assert_eq!(pairs.len(), 10);
+    }
+
+    #[test]
+    fn swiss_pairs_neighbours_without_repeating() {
+        let list = images(4);
+
+        let pairs = super::Swiss { rounds: 2 }.generate(&list);
+
+        let expected_pairs = vec![
+            (list[0].clone(), list[1].clone()),
+            (list[2].clone(), list[3].clone()),
+            (list[0].clone(), list[2].clone()),
+            (list[1].clone(), list[3].clone()),
+        ];
+
+        assert_eq!(pairs, expected_pairs);
+    }
+
+    #[test]
+    fn swiss_rotates_image_left_out_if_odd() {
+        let list = images(5);
+
+        let pairs = super::Swiss { rounds: 2 }.generate(&list);
+
+        let expected_pairs = vec![
+            (list[0].clone(), list[1].clone()),
+            (list[2].clone(), list[3].clone()),
+            (list[0].clone(), list[2].clone()),
+            (list[1].clone(), list[4].clone()),
+        ];
+
+        assert_eq!(pairs, expected_pairs);
+    }
+
+    #[test]
+    fn reference_compares_everything_to_first_image() {
+        let list = images(4);
+
+        let pairs = super::Reference.generate(&list);
+
+        assert_eq!(pairs.len(), 3);
+        for (a, _) in pairs {
+            assert_eq!(a, list[0]);
+        }
+    }
+}
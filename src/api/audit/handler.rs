@@ -0,0 +1,55 @@
+use rocket::{
+    http::Status,
+    request::{
+        FromRequest,
+        Outcome,
+    },
+    serde::json::Json,
+    Request,
+};
+use rocket_db_pools::Connection;
+
+use super::{
+    AuditFilter,
+    AuditPage,
+    AuditTrail,
+};
+use crate::{
+    api::{
+        admin::Owner,
+        QueryError,
+        RequestId,
+    },
+    response::ResponseBody,
+    DbPool,
+};
+
+#[get("/admin/audit?<filter..>")]
+pub(crate) async fn list_audit_entries(
+    admin: Owner,
+    filter: AuditFilter,
+    request_id: &RequestId,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<AuditPage, QueryError>>) {
+    if let Err(error) = admin.check_unscoped_owner() {
+        return (error.default_status(), Json((request_id, Err(error)).into()));
+    }
+
+    match super::list_audit_entries(&filter, &mut **connection).await {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(page) => (Status::Ok, Json((request_id, Ok(page)).into())),
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for &'r AuditTrail {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(
+        request: &'r Request<'_>,
+    ) -> Outcome<Self, Self::Error> {
+        Outcome::Success(AuditTrail::of(request))
+    }
+}
@@ -0,0 +1,267 @@
+pub(crate) mod handler;
+
+use std::{
+    collections::BTreeMap,
+    net::IpAddr,
+    sync::Mutex,
+};
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use rocket::{
+    http::Status,
+    Request,
+};
+use serde::Serialize;
+use sqlx::SqliteConnection;
+
+use super::{
+    comparison::parse_created_at,
+    QueryError,
+    RequestId,
+};
+
+/// What the audit log records about a request made by an `admin`,
+/// collected while it is handled. The `Admin` guard sets `admin_id`, so
+/// only requests authenticated as an `admin` are recorded, including the
+/// ones its role doesn't allow.
+#[derive(Default)]
+pub(crate) struct AuditTrail {
+    admin_id: Mutex<Option<i64>>,
+    affected_rows: Mutex<Option<i64>>,
+}
+
+impl AuditTrail {
+    pub(crate) fn of<'r>(request: &'r Request<'_>) -> &'r AuditTrail {
+        request.local_cache(AuditTrail::default)
+    }
+
+    pub(crate) fn set_admin(&self, admin_id: i64) {
+        *self
+            .admin_id
+            .lock()
+            .expect("BUG: lock should not be poisoned") = Some(admin_id);
+    }
+
+    /// Adds `rows` to the rows created, changed or deleted by the request.
+    pub(crate) fn add_affected_rows(&self, rows: usize) {
+        let mut affected_rows = self
+            .affected_rows
+            .lock()
+            .expect("BUG: lock should not be poisoned");
+        *affected_rows = Some(affected_rows.unwrap_or(0) + rows as i64);
+    }
+
+    pub(crate) fn admin_id(&self) -> Option<i64> {
+        *self
+            .admin_id
+            .lock()
+            .expect("BUG: lock should not be poisoned")
+    }
+
+    fn affected_rows(&self) -> Option<i64> {
+        *self
+            .affected_rows
+            .lock()
+            .expect("BUG: lock should not be poisoned")
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct AuditEntry {
+    pub(crate) id: i64,
+    pub(crate) admin_id: i64,
+    pub(crate) method: String,
+    /// The route matched, with its dynamic segments, or the path when no
+    /// route matched.
+    pub(crate) route: String,
+    pub(crate) path: String,
+    pub(crate) parameters: BTreeMap<String, String>,
+    pub(crate) status: i64,
+    pub(crate) affected_rows: Option<i64>,
+    pub(crate) ip_addr: Option<String>,
+    pub(crate) request_id: i64,
+    pub(crate) created_at: DateTime<Utc>,
+}
+
+/// Filters and page of the audit log. Dates are RFC 3339 timestamps or
+/// `YYYY-MM-DD` days, in UTC.
+#[derive(FromForm)]
+pub(crate) struct AuditFilter {
+    pub(crate) admin_id: Option<i64>,
+    pub(crate) method: Option<String>,
+    /// Only entries whose route starts with this.
+    pub(crate) route: Option<String>,
+    pub(crate) created_after: Option<String>,
+    pub(crate) created_before: Option<String>,
+    #[field(default = 1, validate = range(1..))]
+    pub(crate) page: i64,
+    #[field(default = 50, validate = range(1..=500))]
+    pub(crate) per_page: i64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct AuditPage {
+    pub(crate) entries: Vec<AuditEntry>,
+    pub(crate) page: i64,
+    pub(crate) per_page: i64,
+    pub(crate) total: i64,
+}
+
+/// Returns the dynamic path segments of the route matched by `request`
+/// along with its query fields, by name.
+fn request_parameters(request: &Request<'_>) -> BTreeMap<String, String> {
+    let mut parameters = BTreeMap::new();
+
+    if let Some(route) = request.route() {
+        let route_segments = route
+            .uri
+            .path()
+            .split('/')
+            .filter(|segment| !segment.is_empty());
+        for (route_segment, segment) in
+            route_segments.zip(request.uri().path().segments())
+        {
+            if let Some(name) = route_segment
+                .strip_prefix('<')
+                .and_then(|name| name.strip_suffix('>'))
+            {
+                parameters.insert(
+                    name.trim_end_matches("..").to_string(),
+                    segment.to_string(),
+                );
+            }
+        }
+    }
+
+    for field in request.query_fields() {
+        parameters
+            .insert(field.name.source().to_string(), field.value.to_string());
+    }
+
+    parameters
+}
+
+/// Records `request` in the audit log if it was made by an `admin`.
+pub(crate) async fn record_request(
+    request: &Request<'_>,
+    status: Status,
+    connection: &mut SqliteConnection,
+) -> Result<(), QueryError> {
+    let trail = AuditTrail::of(request);
+    let Some(admin_id) = trail.admin_id() else {
+        return Ok(());
+    };
+
+    let request_id = request
+        .guard::<&RequestId>()
+        .await
+        .expect("BUG: RequestId should return Outcome::Success")
+        .0 as i64;
+    let method = request.method().as_str();
+    let path = request.uri().path().to_string();
+    let route = request
+        .route()
+        .map(|route| route.uri.path().to_string())
+        .unwrap_or_else(|| path.clone());
+    let parameters =
+        rocket::serde::json::to_string(&request_parameters(request))
+            .expect("BUG: a map of strings should serialize");
+    let status = status.code as i64;
+    let affected_rows = trail.affected_rows();
+    let ip_addr = request.client_ip().as_ref().map(IpAddr::to_string);
+
+    sqlx::query!(
+        "INSERT INTO admin_audit (admin_id, method, route, path, parameters, \
+         status, affected_rows, ip_addr, request_id) VALUES (?, ?, ?, ?, ?, \
+         ?, ?, ?, ?)",
+        admin_id,
+        method,
+        route,
+        path,
+        parameters,
+        status,
+        affected_rows,
+        ip_addr,
+        request_id,
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn list_audit_entries(
+    filter: &AuditFilter,
+    connection: &mut SqliteConnection,
+) -> Result<AuditPage, QueryError> {
+    let created_after = filter
+        .created_after
+        .as_deref()
+        .map(|value| parse_created_at("created_after", value))
+        .transpose()?;
+    let created_before = filter
+        .created_before
+        .as_deref()
+        .map(|value| parse_created_at("created_before", value))
+        .transpose()?;
+    let method = filter.method.as_deref().map(str::to_uppercase);
+    let offset = (filter.page - 1) * filter.per_page;
+
+    let total = sqlx::query_scalar!(
+        "SELECT COUNT(*) as \"count: i64\" FROM admin_audit WHERE (?1 IS NULL \
+         OR admin_id = ?1) AND (?2 IS NULL OR method = ?2) AND (?3 IS NULL OR \
+         substr(route, 1, length(?3)) = ?3) AND (?4 IS NULL OR created_at >= \
+         ?4) AND (?5 IS NULL OR created_at < ?5)",
+        filter.admin_id,
+        method,
+        filter.route,
+        created_after,
+        created_before,
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+
+    let entries = sqlx::query!(
+        "SELECT id, admin_id, method, route, path, parameters, status, \
+         affected_rows, ip_addr, request_id, created_at as \"created_at: \
+         DateTime<Utc>\" FROM admin_audit WHERE (?1 IS NULL OR admin_id = ?1) \
+         AND (?2 IS NULL OR method = ?2) AND (?3 IS NULL OR substr(route, 1, \
+         length(?3)) = ?3) AND (?4 IS NULL OR created_at >= ?4) AND (?5 IS \
+         NULL OR created_at < ?5) ORDER BY id LIMIT ?6 OFFSET ?7",
+        filter.admin_id,
+        method,
+        filter.route,
+        created_after,
+        created_before,
+        filter.per_page,
+        offset,
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| AuditEntry {
+        id: row.id,
+        admin_id: row.admin_id,
+        method: row.method,
+        route: row.route,
+        path: row.path,
+        parameters: rocket::serde::json::from_str(&row.parameters)
+            .unwrap_or_default(),
+        status: row.status,
+        affected_rows: row.affected_rows,
+        ip_addr: row.ip_addr,
+        request_id: row.request_id,
+        created_at: row.created_at,
+    })
+    .collect();
+
+    Ok(AuditPage {
+        entries,
+        page: filter.page,
+        per_page: filter.per_page,
+        total,
+    })
+}
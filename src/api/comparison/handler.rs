@@ -1,13 +1,31 @@
 use rocket::{
     http::Status,
     serde::json::Json,
+    State,
 };
 use rocket_db_pools::Connection;
 use uuid::Uuid;
 
-use super::Comparison;
+use super::{
+    AssignedComparison,
+    Comparison,
+    ComparisonDetails,
+    ComparisonFilter,
+    ComparisonPage,
+    DeletionTokens,
+    DirnameDeletion,
+    GoldStandard,
+    ScoreCache,
+};
 use crate::{
     api::{
+        admin::{
+            Curator,
+            Owner,
+            Viewer,
+        },
+        audit::AuditTrail,
+        user::StaleLambdas,
         QueryError,
         RequestId,
     },
@@ -20,12 +38,18 @@ pub(crate) async fn get_comparison_for_user<'r>(
     id: Uuid,
     request_id: &RequestId,
     dirname: Option<String>,
+    score_cache: &State<ScoreCache>,
     mut connection: Connection<DbPool>,
-) -> (Status, Json<ResponseBody<Comparison<'r>, QueryError>>) {
+) -> (Status, Json<ResponseBody<AssignedComparison<'r>, QueryError>>) {
     let user = crate::api::user::get_user(id, &mut **connection).await;
     let dirname = dirname.unwrap_or("".to_string());
-    let comparison =
-        super::get_comparison_for_user(id, dirname, &mut **connection).await;
+    let comparison = super::get_comparison_for_user(
+        id,
+        dirname,
+        score_cache,
+        &mut **connection,
+    )
+    .await;
 
     match (user, comparison) {
         (Err(error), _) => {
@@ -64,3 +88,128 @@ pub(crate) async fn get_comparison_dirnames<'r>(
         Ok(dirnames) => (Status::Ok, Json((request_id, Ok(dirnames)).into())),
     }
 }
+
+#[get("/admin/comparison?<filter..>")]
+pub(crate) async fn list_comparisons<'r>(
+    admin: Viewer,
+    filter: ComparisonFilter,
+    request_id: &RequestId,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<ComparisonPage<'r>, QueryError>>) {
+    match super::list_comparisons(&admin, &filter, &mut **connection).await {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(page) => (Status::Ok, Json((request_id, Ok(page)).into())),
+    }
+}
+
+#[get("/admin/comparison/<id>")]
+pub(crate) async fn get_comparison<'r>(
+    admin: Viewer,
+    id: Uuid,
+    request_id: &RequestId,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<ComparisonDetails<'r>, QueryError>>) {
+    match super::get_comparison_details(id, &admin, &mut **connection).await {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(details) => (Status::Ok, Json((request_id, Ok(details)).into())),
+    }
+}
+
+/// Marks the `comparison` as a gold one with the given expected answer,
+/// or unmarks it.
+#[put(
+    "/admin/comparison/<id>/gold",
+    format = "application/json",
+    data = "<gold_standard>"
+)]
+pub(crate) async fn set_gold_standard<'r>(
+    admin: Curator,
+    id: Uuid,
+    gold_standard: Json<GoldStandard>,
+    request_id: &RequestId,
+    audit: &AuditTrail,
+    stale_lambdas: &State<StaleLambdas>,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<ComparisonDetails<'r>, QueryError>>) {
+    match super::set_gold_standard(
+        id,
+        &gold_standard,
+        &admin,
+        stale_lambdas,
+        &mut **connection,
+    )
+    .await
+    {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(details) => {
+            audit.add_affected_rows(1);
+            (Status::Ok, Json((request_id, Ok(details)).into()))
+        },
+    }
+}
+
+#[delete("/admin/comparison/<id>")]
+pub(crate) async fn delete_comparison<'r>(
+    admin: Curator,
+    id: Uuid,
+    request_id: &RequestId,
+    audit: &AuditTrail,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<Comparison<'r>, QueryError>>) {
+    match super::delete_comparison(id, &admin, &mut **connection).await {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(comparison) => {
+            audit.add_affected_rows(1);
+            (Status::Ok, Json((request_id, Ok(comparison)).into()))
+        },
+    }
+}
+
+/// Without `confirm`, returns what would be deleted along with a token to
+/// confirm the deletion with; with it, deletes every `comparison` of the
+/// dirname and their votes.
+#[delete("/admin/comparison?<dirname>&<confirm>")]
+pub(crate) async fn delete_dirname(
+    admin: Owner,
+    dirname: String,
+    confirm: Option<String>,
+    tokens: &State<DeletionTokens>,
+    request_id: &RequestId,
+    audit: &AuditTrail,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<DirnameDeletion, QueryError>>) {
+    if let Err(error) = admin.check_access(&dirname) {
+        return (error.default_status(), Json((request_id, Err(error)).into()));
+    }
+
+    let deletion = super::delete_dirname(
+        &dirname,
+        confirm.as_deref(),
+        tokens,
+        &mut **connection,
+    )
+    .await;
+
+    match deletion {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(deletion) if confirm.is_none() => {
+            (Status::Accepted, Json((request_id, Ok(deletion)).into()))
+        },
+        Ok(deletion) => {
+            audit.add_affected_rows(
+                (deletion.comparisons + deletion.votes) as usize,
+            );
+            (Status::Ok, Json((request_id, Ok(deletion)).into()))
+        },
+    }
+}
@@ -1,14 +1,47 @@
 pub(crate) mod handler;
 
+use std::{
+    collections::{
+        BTreeMap,
+        HashMap,
+    },
+    fmt::Display,
+    sync::{
+        Arc,
+        Mutex,
+    },
+    time::Instant,
+};
+
 use chrono::{
     DateTime,
+    Duration,
+    NaiveDate,
     Utc,
 };
-use serde::Serialize;
-use sqlx::SqliteConnection;
+use rand::seq::SliceRandom;
+use rocket::tokio::sync::Mutex as AsyncMutex;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sqlx::{
+    Connection,
+    SqliteConnection,
+};
 use uuid::Uuid;
 
 use super::{
+    admin::Admin,
+    project::Question,
+    ranking::{
+        expected_information,
+        fit_bradley_terry,
+        get_contests,
+        Contest,
+    },
+    user::StaleLambdas,
+    vote::VoteValue,
     QueryError,
     SqliteArray,
     SqliteUuid,
@@ -23,43 +56,710 @@ pub(crate) struct Comparison<'a> {
     pub(crate) created_by: i64,
 }
 
+/// A `comparison` handed out to a `user`, along with the question they
+/// are asked about it.
+#[derive(Serialize)]
+pub(crate) struct AssignedComparison<'a> {
+    #[serde(flatten)]
+    pub(crate) comparison: Comparison<'a>,
+    pub(crate) question: Question,
+}
+
+/// How `get_comparison_for_user` picks among the `comparison`s the `user`
+/// has not voted on yet.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SelectionStrategy {
+    /// Uniformly at random.
+    Random,
+    /// The ones with the fewest votes so far, ties broken at random, so
+    /// all `comparison`s of a dirname get an even number of votes.
+    Balanced,
+    /// The one with the highest expected information gain under the
+    /// current Bradley–Terry ranking of the dirname, ties broken at random.
+    Informative,
+}
+
+impl From<String> for SelectionStrategy {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "balanced" => SelectionStrategy::Balanced,
+            "informative" => SelectionStrategy::Informative,
+            _ => SelectionStrategy::Random,
+        }
+    }
+}
+
+impl Display for SelectionStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SelectionStrategy::Random => "random",
+            SelectionStrategy::Balanced => "balanced",
+            SelectionStrategy::Informative => "informative",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Picks the next `comparison` of the dirname for the `user` and records
+/// when it was handed out, so their vote on it can be timed. At the
+/// `gold_rate` of the dirname, it is a gold `comparison` the `user` has
+/// not voted on yet, if any is left.
 async fn get_comparison_for_user<'r>(
     user_id: Uuid,
     dirname: String,
+    score_cache: &ScoreCache,
+    connection: &mut SqliteConnection,
+) -> Result<AssignedComparison<'r>, QueryError> {
+    let project =
+        super::project::get_project(&dirname, &mut *connection).await?;
+    let gold_comparison = if rand::random::<f64>() < project.gold_rate {
+        pick_gold_comparison(user_id, &dirname, &mut *connection).await?
+    } else {
+        None
+    };
+    let comparison = match gold_comparison {
+        Some(comparison) => comparison,
+        None => {
+            pick_comparison_for_user(
+                user_id,
+                dirname,
+                project.selection_strategy,
+                score_cache,
+                &mut *connection,
+            )
+            .await?
+        },
+    };
+
+    sqlx::query!(
+        "INSERT INTO assignment (comparison_id, user_id) VALUES (?1, ?2) ON \
+         CONFLICT DO UPDATE SET issued_at = strftime('%Y-%m-%d %H:%M:%f', \
+         'now')",
+        *comparison.id,
+        user_id,
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(AssignedComparison {
+        comparison,
+        question: project.into(),
+    })
+}
+
+async fn pick_comparison_for_user<'r>(
+    user_id: Uuid,
+    dirname: String,
+    selection_strategy: SelectionStrategy,
+    score_cache: &ScoreCache,
     connection: &mut SqliteConnection,
 ) -> Result<Comparison<'r>, QueryError> {
+    let comparison = match selection_strategy {
+        SelectionStrategy::Random => {
+            sqlx::query_as!(
+                Comparison,
+                "SELECT id, dirname, images, created_at as \"created_at: _\", \
+                 created_by FROM comparison WHERE comparison.dirname = ?1 AND \
+                 comparison.retired_at IS NULL AND \
+                 comparison.expected_vote_value IS NULL AND comparison.id NOT \
+                 IN (SELECT comparison_id FROM vote WHERE user_id = ?2 AND \
+                 retracted_at IS NULL) ORDER BY RANDOM() LIMIT 1",
+                dirname,
+                user_id,
+            )
+            .fetch_one(connection)
+            .await
+        },
+        SelectionStrategy::Balanced => {
+            sqlx::query_as!(
+                Comparison,
+                "SELECT id, dirname, images, created_at as \"created_at: _\", \
+                 created_by FROM comparison WHERE comparison.dirname = ?1 AND \
+                 comparison.retired_at IS NULL AND \
+                 comparison.expected_vote_value IS NULL AND comparison.id NOT \
+                 IN (SELECT comparison_id FROM vote WHERE user_id = ?2 AND \
+                 retracted_at IS NULL) ORDER BY (SELECT COUNT(vote.id) FROM \
+                 vote WHERE vote.comparison_id = comparison.id AND \
+                 vote.retracted_at IS NULL), RANDOM() LIMIT 1",
+                dirname,
+                user_id,
+            )
+            .fetch_one(connection)
+            .await
+        },
+        SelectionStrategy::Informative => {
+            return get_most_informative_comparison(
+                user_id,
+                dirname,
+                score_cache,
+                connection,
+            )
+            .await;
+        },
+    };
+
+    comparison.map_err(|error| match error {
+        sqlx::Error::RowNotFound => QueryError::RowNotFound(
+            "No `comparison` available for `user`".to_string(),
+        ),
+        error => error.into(),
+    })
+}
+
+/// Picks at random a gold `comparison` of the dirname the `user` has not
+/// voted on yet.
+async fn pick_gold_comparison<'r>(
+    user_id: Uuid,
+    dirname: &str,
+    connection: &mut SqliteConnection,
+) -> Result<Option<Comparison<'r>>, QueryError> {
     sqlx::query_as!(
         Comparison,
         "SELECT id, dirname, images, created_at as \"created_at: _\", \
          created_by FROM comparison WHERE comparison.dirname = ?1 AND \
-         comparison.id NOT IN (SELECT comparison_id FROM vote WHERE user_id = \
-         ?2) ORDER BY RANDOM() LIMIT 1",
+         comparison.retired_at IS NULL AND comparison.expected_vote_value IS \
+         NOT NULL AND comparison.id NOT IN (SELECT comparison_id FROM vote \
+         WHERE user_id = ?2 AND retracted_at IS NULL) ORDER BY RANDOM() LIMIT \
+         1",
+        dirname,
+        user_id,
+    )
+    .fetch_optional(connection)
+    .await
+    .map_err(|error| error.into())
+}
+
+/// Picks the `comparison` the `user` has not voted on yet whose vote is
+/// expected to tell the most about the ranking, from the scores cached in
+/// the `score_cache`.
+async fn get_most_informative_comparison<'r>(
+    user_id: Uuid,
+    dirname: String,
+    score_cache: &ScoreCache,
+    connection: &mut SqliteConnection,
+) -> Result<Comparison<'r>, QueryError> {
+    let mut comparisons = sqlx::query_as!(
+        Comparison,
+        "SELECT id, dirname, images, created_at as \"created_at: _\", \
+         created_by FROM comparison WHERE comparison.dirname = ?1 AND \
+         comparison.retired_at IS NULL AND comparison.expected_vote_value IS \
+         NULL AND comparison.id NOT IN (SELECT comparison_id FROM vote WHERE \
+         user_id = ?2 AND retracted_at IS NULL)",
         dirname,
         user_id,
     )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    if comparisons.is_empty() {
+        return Err(QueryError::RowNotFound(
+            "No `comparison` available for `user`".to_string(),
+        ));
+    }
+
+    let fitted = score_cache.get(&dirname, connection).await?;
+    let FittedScores {
+        indexes, scores, ..
+    } = &*fitted;
+
+    // a vote on more than two images brings information on every pair of
+    // them
+    let information = |comparison: &Comparison| -> f64 {
+        let items: Option<Vec<usize>> = comparison
+            .images
+            .iter()
+            .map(|image| indexes.get(image.path().as_str()).copied())
+            .collect();
+
+        items.map_or(0.0, |items| {
+            items
+                .iter()
+                .enumerate()
+                .flat_map(|(i, &a)| {
+                    items[(i + 1)..].iter().map(move |&b| (a, b))
+                })
+                .map(|(a, b)| expected_information(scores, a, b))
+                .sum()
+        })
+    };
+
+    comparisons.shuffle(&mut rand::thread_rng());
+    let index = comparisons
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| information(a).total_cmp(&information(b)))
+        .map(|(index, _)| index)
+        .expect("BUG: comparisons should not be empty");
+
+    Ok(comparisons.swap_remove(index))
+}
+
+async fn get_comparison_dirnames(
+    connection: &mut SqliteConnection,
+) -> Result<Vec<String>, QueryError> {
+    sqlx::query!(
+        "SELECT DISTINCT dirname FROM comparison WHERE retired_at IS NULL"
+    )
+    .fetch_all(connection)
+    .await
+    .map_err(|error| match error {
+        sqlx::Error::RowNotFound => {
+            QueryError::RowNotFound("No `comparison`s available".to_string())
+        },
+        error => error.into(),
+    })
+    .map(|results| results.iter().map(|entry| entry.dirname.clone()).collect())
+}
+
+/// Filters and page of the `comparison`s listed to admins. Dates are
+/// RFC 3339 timestamps or `YYYY-MM-DD` days, in UTC.
+#[derive(FromForm)]
+pub(crate) struct ComparisonFilter {
+    pub(crate) dirname: Option<String>,
+    pub(crate) created_by: Option<i64>,
+    pub(crate) created_after: Option<String>,
+    pub(crate) created_before: Option<String>,
+    #[field(default = 1, validate = range(1..))]
+    pub(crate) page: i64,
+    #[field(default = 50, validate = range(1..=500))]
+    pub(crate) per_page: i64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ComparisonPage<'a> {
+    pub(crate) comparisons: Vec<Comparison<'a>>,
+    pub(crate) page: i64,
+    pub(crate) per_page: i64,
+    pub(crate) total: i64,
+}
+
+/// A `comparison` with the number of votes for each `vote_value`.
+#[derive(Serialize)]
+pub(crate) struct ComparisonDetails<'a> {
+    #[serde(flatten)]
+    pub(crate) comparison: Comparison<'a>,
+    pub(crate) retired_at: Option<DateTime<Utc>>,
+    /// The known correct answer of a gold `comparison`.
+    pub(crate) expected_vote_value: Option<VoteValue>,
+    pub(crate) vote_count: i64,
+    pub(crate) votes: BTreeMap<String, i64>,
+}
+
+/// Marks a `comparison` as a gold one with its known correct answer, or
+/// unmarks it when null.
+#[derive(Deserialize)]
+pub(crate) struct GoldStandard {
+    pub(crate) expected_vote_value: Option<VoteValue>,
+}
+
+/// What deleting every `comparison` of a dirname removes.
+#[derive(Serialize)]
+pub(crate) struct DirnameDeletion {
+    pub(crate) dirname: String,
+    pub(crate) comparisons: i64,
+    pub(crate) votes: i64,
+    /// Token to send back as `confirm` to carry out the deletion.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) confirm: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) expires_at: Option<DateTime<Utc>>,
+}
+
+/// Confirmation tokens issued for deleting dirnames, by token, with the
+/// dirname they allow deleting and when they expire.
+#[derive(Default)]
+pub(crate) struct DeletionTokens(
+    Mutex<HashMap<String, (String, DateTime<Utc>)>>,
+);
+
+const DELETION_TOKEN_LIFETIME_MINUTES: i64 = 5;
+
+/// Bradley–Terry scores fitted on the votes of each dirname for the
+/// informative selection, so handing out a `comparison` doesn't refit the
+/// model. They are refitted once older than `SCORE_LIFETIME_SECONDS`, by
+/// one request at a time, the others waiting for its scores.
+#[derive(Default)]
+pub(crate) struct ScoreCache(Mutex<HashMap<String, Arc<CachedScores>>>);
+
+type CachedScores = AsyncMutex<Option<Arc<FittedScores>>>;
+
+const SCORE_LIFETIME_SECONDS: u64 = 30;
+
+/// Scores of the images of a dirname, along with their index by path.
+struct FittedScores {
+    indexes: HashMap<String, usize>,
+    scores: Vec<(f64, f64)>,
+    fitted_at: Instant,
+}
+
+impl ScoreCache {
+    /// Returns the scores fitted for `dirname`, fitting them again if
+    /// missing or expired.
+    async fn get(
+        &self,
+        dirname: &str,
+        connection: &mut SqliteConnection,
+    ) -> Result<Arc<FittedScores>, QueryError> {
+        let entry = self
+            .0
+            .lock()
+            .expect("BUG: lock should not be poisoned")
+            .entry(dirname.to_string())
+            .or_default()
+            .clone();
+        let mut entry = entry.lock().await;
+
+        if let Some(fitted) = entry.as_ref().filter(|fitted| {
+            fitted.fitted_at.elapsed().as_secs() < SCORE_LIFETIME_SECONDS
+        }) {
+            return Ok(fitted.clone());
+        }
+
+        let (images, votes) = get_contests(dirname, connection).await?;
+        let contests: Vec<Contest> =
+            votes.into_iter().map(|(_, contest)| contest).collect();
+        let fitted = Arc::new(FittedScores {
+            indexes: images
+                .iter()
+                .enumerate()
+                .map(|(index, image)| (image.path().to_string(), index))
+                .collect(),
+            scores: fit_bradley_terry(images.len(), &contests),
+            fitted_at: Instant::now(),
+        });
+        *entry = Some(fitted.clone());
+
+        Ok(fitted)
+    }
+}
+
+impl DeletionTokens {
+    fn issue(&self, dirname: &str) -> (String, DateTime<Utc>) {
+        let token = Uuid::new_v4().simple().to_string();
+        let expires_at =
+            Utc::now() + Duration::minutes(DELETION_TOKEN_LIFETIME_MINUTES);
+
+        let mut tokens =
+            self.0.lock().expect("BUG: lock should not be poisoned");
+        tokens.retain(|_, (_, expires_at)| *expires_at > Utc::now());
+        tokens.insert(token.clone(), (dirname.to_string(), expires_at));
+
+        (token, expires_at)
+    }
+
+    /// Consumes `token` if it was issued for `dirname` and has not expired.
+    fn redeem(&self, token: &str, dirname: &str) -> bool {
+        let mut tokens =
+            self.0.lock().expect("BUG: lock should not be poisoned");
+        match tokens.remove(token) {
+            Some((issued_for, expires_at)) => {
+                issued_for == dirname && expires_at > Utc::now()
+            },
+            None => false,
+        }
+    }
+}
+
+/// Parses an RFC 3339 timestamp or `YYYY-MM-DD` day into the format of
+/// the `created_at` columns.
+pub(crate) fn parse_created_at(
+    name: &str,
+    value: &str,
+) -> Result<String, QueryError> {
+    let timestamp = DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.with_timezone(&Utc).naive_utc())
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|day| {
+                day.and_hms_opt(0, 0, 0)
+                    .expect("BUG: midnight should be valid")
+            })
+        })
+        .map_err(|_| {
+            QueryError::InvalidInput(format!(
+                "`{name}` must be an RFC 3339 timestamp or a YYYY-MM-DD day"
+            ))
+        })?;
+
+    Ok(timestamp.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// Lists the `comparison`s matching `filter` among the ones of the
+/// dirnames `admin` can access.
+pub(crate) async fn list_comparisons<'r>(
+    admin: &Admin,
+    filter: &ComparisonFilter,
+    connection: &mut SqliteConnection,
+) -> Result<ComparisonPage<'r>, QueryError> {
+    let created_after = filter
+        .created_after
+        .as_deref()
+        .map(|value| parse_created_at("created_after", value))
+        .transpose()?;
+    let created_before = filter
+        .created_before
+        .as_deref()
+        .map(|value| parse_created_at("created_before", value))
+        .transpose()?;
+    let offset = (filter.page - 1) * filter.per_page;
+    if let Some(dirname) = &filter.dirname {
+        admin.check_access(dirname)?;
+    }
+
+    let total = sqlx::query_scalar!(
+        "SELECT COUNT(*) as \"count: i64\" FROM comparison WHERE (?1 IS NULL \
+         OR dirname = ?1) AND (?2 IS NULL OR created_by = ?2) AND (?3 IS NULL \
+         OR created_at >= ?3) AND (?4 IS NULL OR created_at < ?4) AND EXISTS \
+         (SELECT 1 FROM admin_comparison WHERE admin_id = ?5 AND \
+         comparison_id = comparison.id)",
+        filter.dirname,
+        filter.created_by,
+        created_after,
+        created_before,
+        admin.id,
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+
+    let comparisons = sqlx::query_as!(
+        Comparison,
+        "SELECT id, dirname, images, created_at as \"created_at: _\", \
+         created_by FROM comparison WHERE (?1 IS NULL OR dirname = ?1) AND \
+         (?2 IS NULL OR created_by = ?2) AND (?3 IS NULL OR created_at >= ?3) \
+         AND (?4 IS NULL OR created_at < ?4) AND EXISTS (SELECT 1 FROM \
+         admin_comparison WHERE admin_id = ?7 AND comparison_id = \
+         comparison.id) ORDER BY created_at, dirname, images LIMIT ?5 OFFSET \
+         ?6",
+        filter.dirname,
+        filter.created_by,
+        created_after,
+        created_before,
+        filter.per_page,
+        offset,
+        admin.id,
+    )
+    .fetch_all(connection)
+    .await?;
+
+    Ok(ComparisonPage {
+        comparisons,
+        page: filter.page,
+        per_page: filter.per_page,
+        total,
+    })
+}
+
+pub(crate) async fn get_comparison_details<'r>(
+    id: Uuid,
+    admin: &Admin,
+    connection: &mut SqliteConnection,
+) -> Result<ComparisonDetails<'r>, QueryError> {
+    let row = sqlx::query!(
+        "SELECT id, dirname, images, created_at as \"created_at: \
+         DateTime<Utc>\", created_by, retired_at as \"retired_at: \
+         DateTime<Utc>\", expected_vote_value FROM comparison WHERE id = ?",
+        id,
+    )
+    .fetch_one(&mut *connection)
+    .await
+    .map_err(|error| match error {
+        sqlx::Error::RowNotFound => QueryError::RowNotFound(
+            "`comparison` with requested id not found".to_string(),
+        ),
+        error => error.into(),
+    })?;
+    admin.check_access(&row.dirname)?;
+
+    let votes: BTreeMap<String, i64> = sqlx::query!(
+        "SELECT vote_value, COUNT(*) as \"count: i64\" FROM vote WHERE \
+         comparison_id = ? AND retracted_at IS NULL GROUP BY vote_value",
+        id,
+    )
+    .fetch_all(connection)
+    .await?
+    .into_iter()
+    .map(|row| (row.vote_value, row.count))
+    .collect();
+
+    Ok(ComparisonDetails {
+        comparison: Comparison {
+            id: row.id.into(),
+            dirname: row.dirname,
+            images: row.images.into(),
+            created_at: row.created_at,
+            created_by: row.created_by,
+        },
+        retired_at: row.retired_at,
+        expected_vote_value: row.expected_vote_value.map(VoteValue::from),
+        vote_count: votes.values().sum(),
+        votes,
+    })
+}
+
+/// Sets the `expected_vote_value` of a `comparison`, which turns it into a
+/// gold one only handed out at the `gold_rate` of its dirname and left
+/// out of its ranking, and records whether the votes already cast on it
+/// gave that answer. The `user`s who cast them are marked for an
+/// `average_lambda` refresh, since their votes moved in or out of the
+/// ranking.
+pub(crate) async fn set_gold_standard<'r>(
+    id: Uuid,
+    gold_standard: &GoldStandard,
+    admin: &Admin,
+    stale_lambdas: &StaleLambdas,
+    connection: &mut SqliteConnection,
+) -> Result<ComparisonDetails<'r>, QueryError> {
+    let _ = get_comparison_details(id, admin, &mut *connection).await?;
+
+    if let Some(vote_value) = &gold_standard.expected_vote_value {
+        if let VoteValue::CannotTell = vote_value {
+            return Err(QueryError::InvalidInput(
+                "`cannot_tell` can't be the `expected_vote_value`".to_string(),
+            ));
+        }
+        super::vote::check_vote_value(id, vote_value, &mut *connection)
+            .await
+            .map_err(|error| match error {
+                QueryError::RowNotFound(message) => {
+                    QueryError::InvalidInput(message)
+                },
+                error => error,
+            })?;
+    }
+
+    let expected_vote_value = gold_standard
+        .expected_vote_value
+        .as_ref()
+        .map(|vote_value| vote_value.to_string());
+
+    let mut transaction = connection.begin().await?;
+    sqlx::query!(
+        "UPDATE comparison SET expected_vote_value = ? WHERE id = ?",
+        expected_vote_value,
+        id,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "UPDATE vote SET gold_passed = (?1 = vote_value) WHERE comparison_id \
+         = ?2",
+        expected_vote_value,
+        id,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    let user_ids = sqlx::query_scalar!(
+        "SELECT DISTINCT user_id FROM vote WHERE comparison_id = ?",
+        id,
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+
+    stale_lambdas.mark(
+        user_ids
+            .into_iter()
+            .map(|user_id| *SqliteUuid::from(user_id)),
+    );
+
+    get_comparison_details(id, admin, connection).await
+}
+
+/// Deletes a `comparison` along with its votes.
+pub(crate) async fn delete_comparison<'r>(
+    id: Uuid,
+    admin: &Admin,
+    connection: &mut SqliteConnection,
+) -> Result<Comparison<'r>, QueryError> {
+    let dirname =
+        sqlx::query_scalar!("SELECT dirname FROM comparison WHERE id = ?", id)
+            .fetch_optional(&mut *connection)
+            .await?;
+    if let Some(dirname) = dirname {
+        admin.check_access(&dirname)?;
+    }
+
+    sqlx::query_as!(
+        Comparison,
+        "DELETE FROM comparison WHERE id = ? RETURNING id, dirname, images, \
+         created_at as \"created_at: _\", created_by",
+        id,
+    )
     .fetch_one(connection)
     .await
     .map_err(|error| match error {
         sqlx::Error::RowNotFound => QueryError::RowNotFound(
-            "No `comparison` available for `user`".to_string(),
+            "`comparison` with requested id not found".to_string(),
         ),
         error => error.into(),
     })
 }
 
-async fn get_comparison_dirnames(
+async fn count_dirname(
+    dirname: &str,
     connection: &mut SqliteConnection,
-) -> Result<Vec<String>, QueryError> {
-    sqlx::query!("SELECT DISTINCT dirname FROM comparison")
-        .fetch_all(connection)
-        .await
-        .map_err(|error| match error {
-            sqlx::Error::RowNotFound => QueryError::RowNotFound(
-                "No `comparison`s available".to_string(),
-            ),
-            error => error.into(),
-        })
-        .map(|results| {
-            results.iter().map(|entry| entry.dirname.clone()).collect()
-        })
+) -> Result<(i64, i64), QueryError> {
+    let counts = sqlx::query!(
+        "SELECT COUNT(DISTINCT comparison.id) as \"comparisons: i64\", \
+         COUNT(vote.id) as \"votes: i64\" FROM comparison LEFT JOIN vote ON \
+         vote.comparison_id = comparison.id WHERE comparison.dirname = ?",
+        dirname,
+    )
+    .fetch_one(connection)
+    .await?;
+
+    if counts.comparisons == 0 {
+        return Err(QueryError::RowNotFound(
+            "No `comparison`s found for requested `dirname`".to_string(),
+        ));
+    }
+
+    Ok((counts.comparisons, counts.votes))
+}
+
+/// Deletes every `comparison` of `dirname` along with their votes, if
+/// `confirm` is a token issued for it; otherwise issues such a token and
+/// only reports what would be deleted. The deletion reports what it
+/// actually deleted, counted in the transaction deleting it.
+pub(crate) async fn delete_dirname(
+    dirname: &str,
+    confirm: Option<&str>,
+    tokens: &DeletionTokens,
+    connection: &mut SqliteConnection,
+) -> Result<DirnameDeletion, QueryError> {
+    let Some(token) = confirm else {
+        let (comparisons, votes) = count_dirname(dirname, connection).await?;
+        let (token, expires_at) = tokens.issue(dirname);
+        return Ok(DirnameDeletion {
+            dirname: dirname.to_string(),
+            comparisons,
+            votes,
+            confirm: Some(token),
+            expires_at: Some(expires_at),
+        });
+    };
+
+    if !tokens.redeem(token, dirname) {
+        return Err(QueryError::InvalidInput(
+            "`confirm` token is invalid or expired for requested `dirname`"
+                .to_string(),
+        ));
+    }
+
+    let mut transaction = connection.begin().await?;
+    let (_, votes) = count_dirname(dirname, &mut transaction).await?;
+    let comparisons =
+        sqlx::query!("DELETE FROM comparison WHERE dirname = ?", dirname)
+            .execute(&mut *transaction)
+            .await?
+            .rows_affected() as i64;
+    transaction.commit().await?;
+
+    Ok(DirnameDeletion {
+        dirname: dirname.to_string(),
+        comparisons,
+        votes,
+        confirm: None,
+        expires_at: None,
+    })
 }
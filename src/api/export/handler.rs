@@ -0,0 +1,100 @@
+use rocket::{
+    futures::StreamExt,
+    http::{
+        ContentType,
+        Status,
+    },
+    response::stream::TextStream,
+};
+use rocket_db_pools::Connection;
+
+use super::{
+    ExportFormat,
+    ExportedVote,
+};
+use crate::{
+    api::admin::Viewer,
+    DbPool,
+};
+
+/// Streams every vote joined with its `comparison`, one row at a time, so
+/// the whole table is never loaded into memory. Database errors can only
+/// end the stream early, since the status has already been sent. Only
+/// votes of the dirnames the `admin` can access are exported.
+#[get("/admin/votes/export?<format>&<dirname>&<ip_addr>")]
+pub(crate) async fn export_votes(
+    admin: Viewer,
+    format: ExportFormat,
+    dirname: Option<String>,
+    ip_addr: Option<bool>,
+    mut connection: Connection<DbPool>,
+) -> Result<(ContentType, TextStream![String]), Status> {
+    if let Some(dirname) = &dirname {
+        admin.check_access(dirname).map_err(|_| Status::Forbidden)?;
+    }
+
+    let include_ip_addr = ip_addr.unwrap_or(false);
+    let content_type = match format {
+        ExportFormat::Csv => ContentType::CSV,
+        ExportFormat::Jsonl => ContentType::new("application", "x-ndjson"),
+    };
+
+    let stream = TextStream! {
+        if let ExportFormat::Csv = format {
+            yield ExportedVote::csv_header(include_ip_addr);
+        }
+
+        let mut votes = sqlx::query_as!(
+            ExportedVote,
+            "SELECT vote.id as \"id!\", vote.comparison_id, \
+             comparison.dirname, comparison.images, vote.vote_value, \
+             vote.user_id, vote.created_at as \"created_at: _\", \
+             vote.repetition, COALESCE((SELECT vote_edit.vote_value FROM \
+             vote_edit WHERE vote_edit.vote_id = vote.id ORDER BY vote_edit.id \
+             LIMIT 1), vote.vote_value) as \"original_vote_value!: String\", \
+             vote.retracted_at as \"retracted_at: _\", vote.response_time_ms, \
+             vote.viewport_width, vote.viewport_height, \
+             vote.device_pixel_ratio, vote.confidence, vote.client_created_at \
+             as \"client_created_at: _\", vote.gold_passed as \"gold_passed: \
+             bool\", (SELECT AVG(gold_vote.gold_passed) FROM vote as gold_vote \
+             WHERE gold_vote.user_id = vote.user_id AND gold_vote.gold_passed \
+             IS NOT NULL AND gold_vote.retracted_at IS NULL) as \
+             \"user_reliability?: f64\", vote.ip_addr FROM vote INNER JOIN \
+             comparison ON vote.comparison_id = comparison.id WHERE (?1 IS \
+             NULL OR comparison.dirname = ?1) AND EXISTS (SELECT 1 FROM \
+             admin_comparison WHERE admin_id = ?2 AND comparison_id = \
+             comparison.id) ORDER BY vote.id",
+            dirname,
+            admin.id,
+        )
+        .fetch(&mut **connection);
+
+        while let Some(vote) = votes.next().await {
+            let mut vote = match vote {
+                Ok(vote) => vote,
+                Err(error) => {
+                    error!("Vote export failed: {error}");
+                    break;
+                },
+            };
+            if !include_ip_addr {
+                vote.ip_addr = None;
+            }
+
+            match format {
+                ExportFormat::Csv => {
+                    yield vote.to_csv_record(include_ip_addr);
+                },
+                ExportFormat::Jsonl => match vote.to_jsonl_record() {
+                    Ok(line) => yield line,
+                    Err(error) => {
+                        error!("Vote export failed: {error}");
+                        break;
+                    },
+                },
+            }
+        }
+    };
+
+    Ok((content_type, stream))
+}
@@ -0,0 +1,184 @@
+pub(crate) mod handler;
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use rocket::serde::json;
+use serde::Serialize;
+
+use super::{
+    vote::VoteValue,
+    SqliteArray,
+    SqliteUuid,
+};
+
+#[derive(FromFormField)]
+pub(crate) enum ExportFormat {
+    Csv,
+    Jsonl,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ExportedVote<'a> {
+    pub(crate) id: i64,
+    pub(crate) comparison_id: SqliteUuid,
+    pub(crate) dirname: String,
+    pub(crate) images: SqliteArray<'a>,
+    pub(crate) vote_value: VoteValue,
+    pub(crate) user_id: SqliteUuid,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) repetition: i64,
+    /// The `vote_value` the `user` first gave, before changing it.
+    pub(crate) original_vote_value: VoteValue,
+    pub(crate) retracted_at: Option<DateTime<Utc>>,
+    pub(crate) response_time_ms: Option<i64>,
+    pub(crate) viewport_width: Option<i64>,
+    pub(crate) viewport_height: Option<i64>,
+    pub(crate) device_pixel_ratio: Option<f64>,
+    pub(crate) confidence: Option<i64>,
+    pub(crate) client_created_at: Option<DateTime<Utc>>,
+    /// Whether the `vote_value` is the expected one, for votes on gold
+    /// `comparison`s.
+    pub(crate) gold_passed: Option<bool>,
+    /// The `reliability` of the `user` who cast the vote.
+    pub(crate) user_reliability: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) ip_addr: Option<String>,
+}
+
+impl<'a> ExportedVote<'a> {
+    pub(crate) fn csv_header(include_ip_addr: bool) -> String {
+        let mut header =
+            "id,comparison_id,dirname,images,vote_value,user_id,created_at,\
+             repetition,original_vote_value,retracted_at,response_time_ms,\
+             viewport_width,viewport_height,device_pixel_ratio,confidence,\
+             client_created_at,ranking,gold_passed,user_reliability"
+                .to_string();
+        if include_ip_addr {
+            header.push_str(",ip_addr");
+        }
+        header.push('\n');
+        header
+    }
+
+    /// Formats the vote as a JSON line, along with its `ranking`.
+    pub(crate) fn to_jsonl_record(
+        &self,
+    ) -> Result<String, json::serde_json::Error> {
+        let record = JsonlRecord {
+            vote: self,
+            ranking: self.ranking(),
+        };
+
+        json::to_string(&record).map(|line| format!("{line}\n"))
+    }
+
+    /// Formats the vote as a CSV record. The `images` are joined by a
+    /// space, which cannot appear unencoded in their paths, and the tiers
+    /// of the `ranking` by ` > `.
+    pub(crate) fn to_csv_record(&self, include_ip_addr: bool) -> String {
+        let images: Vec<&str> = self
+            .images
+            .iter()
+            .map(|image| image.path().as_str())
+            .collect();
+
+        let mut fields = vec![
+            self.id.to_string(),
+            self.comparison_id.to_string(),
+            self.dirname.clone(),
+            images.join(" "),
+            self.vote_value.to_string(),
+            self.user_id.to_string(),
+            self.created_at.to_rfc3339(),
+            self.repetition.to_string(),
+            self.original_vote_value.to_string(),
+            self.retracted_at
+                .map(|retracted_at| retracted_at.to_rfc3339())
+                .unwrap_or_default(),
+            optional_field(self.response_time_ms),
+            optional_field(self.viewport_width),
+            optional_field(self.viewport_height),
+            optional_field(self.device_pixel_ratio),
+            optional_field(self.confidence),
+            self.client_created_at
+                .map(|client_created_at| client_created_at.to_rfc3339())
+                .unwrap_or_default(),
+            self.ranking()
+                .iter()
+                .map(|tier| tier.join(" "))
+                .collect::<Vec<String>>()
+                .join(" > "),
+            optional_field(self.gold_passed),
+            optional_field(self.user_reliability),
+        ];
+        if include_ip_addr {
+            fields.push(self.ip_addr.clone().unwrap_or_default());
+        }
+
+        let mut record = fields
+            .iter()
+            .map(|field| escape_csv_field(field))
+            .collect::<Vec<String>>()
+            .join(",");
+        record.push('\n');
+        record
+    }
+
+    /// The `images` in the order the `vote_value` puts them, from most to
+    /// least preferred, in tiers of images it leaves unordered among
+    /// themselves: the partial ranking a Plackett–Luce model is fitted
+    /// on. A single tier means the images were voted equal, and no tiers
+    /// that the vote orders nothing.
+    pub(crate) fn ranking(&self) -> Vec<Vec<&str>> {
+        let paths: Vec<&str> = self
+            .images
+            .iter()
+            .map(|image| image.path().as_str())
+            .collect();
+
+        self.vote_value
+            .tiers(&paths)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tier| tier.into_iter().map(|index| paths[index]).collect())
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+struct JsonlRecord<'v, 'a> {
+    #[serde(flatten)]
+    vote: &'v ExportedVote<'a>,
+    ranking: Vec<Vec<&'v str>>,
+}
+
+fn optional_field<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn escape_csv_field_quotes_only_when_needed() {
+        let tests = vec![
+            ("folder_a", "folder_a"),
+            ("folder,a", "\"folder,a\""),
+            ("folder \"a\"", "\"folder \"\"a\"\"\""),
+            ("folder\na", "\"folder\na\""),
+        ];
+
+        for (test, expected_field) in tests {
+            assert_eq!(super::escape_csv_field(test), expected_field);
+        }
+    }
+}
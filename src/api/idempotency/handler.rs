@@ -0,0 +1,38 @@
+use rocket::{
+    http::Status,
+    request::{
+        FromRequest,
+        Outcome,
+    },
+    Request,
+};
+
+use super::{
+    IdempotencyKey,
+    MAX_KEY_LENGTH,
+};
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IdempotencyKey {
+    type Error = String;
+
+    async fn from_request(
+        request: &'r Request<'_>,
+    ) -> Outcome<Self, Self::Error> {
+        match request.headers().get_one("Idempotency-Key") {
+            None => Outcome::Success(IdempotencyKey(None)),
+            Some(key) if key.is_empty() || key.len() > MAX_KEY_LENGTH => {
+                Outcome::Error((
+                    Status::UnprocessableEntity,
+                    format!(
+                        "`Idempotency-Key` must have 1 to {MAX_KEY_LENGTH} \
+                         characters"
+                    ),
+                ))
+            },
+            Some(key) => {
+                Outcome::Success(IdempotencyKey(Some(key.to_string())))
+            },
+        }
+    }
+}
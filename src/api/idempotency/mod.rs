@@ -0,0 +1,195 @@
+pub(crate) mod handler;
+
+use rocket::{
+    http::Status,
+    response::content::RawJson,
+    serde::json::Json,
+};
+use serde::Serialize;
+use sha2::{
+    Digest,
+    Sha256,
+};
+use sqlx::{
+    Sqlite,
+    SqliteConnection,
+    Transaction,
+};
+use uuid::Uuid;
+
+use super::{
+    QueryError,
+    RequestId,
+};
+use crate::response::ResponseBody;
+
+/// How long a response is replayed for requests repeating its
+/// `Idempotency-Key`, as an SQLite datetime modifier.
+const RETENTION: &str = "-1 day";
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Value of the `Idempotency-Key` header of a request, if it has one.
+pub(crate) struct IdempotencyKey(pub(crate) Option<String>);
+
+/// Response of a handler honoring `IdempotencyKey`s: either the one it
+/// just produced or the one stored for the repeated key.
+#[derive(Responder)]
+pub(crate) enum Idempotent<T> {
+    Fresh((Status, Json<T>)),
+    Replayed((Status, RawJson<String>)),
+}
+
+impl<T> From<(Status, Json<T>)> for Idempotent<T> {
+    fn from(response: (Status, Json<T>)) -> Self {
+        Self::Fresh(response)
+    }
+}
+
+impl IdempotencyKey {
+    /// Fails unless the key, if any, is a UUID. Callers only told apart
+    /// by their address, like clients behind the same NAT, can then not
+    /// pick the same key by chance.
+    pub(crate) fn check_uuid(&self) -> Result<(), QueryError> {
+        match &self.0 {
+            Some(key) if Uuid::parse_str(key).is_err() => {
+                Err(QueryError::InvalidInput(
+                    "`Idempotency-Key` must be a UUID".to_string(),
+                ))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Reserves this key on `route` for `caller`, in the `transaction` the
+    /// request is handled in, so a concurrent request repeating it waits
+    /// for the response instead of being handled again. Returns the
+    /// response stored for the key within the retention window, if any,
+    /// and fails if the key was used for another `request` body.
+    pub(crate) async fn reserve<T>(
+        &self,
+        route: &str,
+        caller: &str,
+        request: &str,
+        transaction: &mut SqliteConnection,
+    ) -> Result<Option<Idempotent<T>>, QueryError> {
+        let Some(key) = &self.0 else {
+            return Ok(None);
+        };
+        let request_hash = hex::encode(Sha256::digest(request.as_bytes()));
+
+        sqlx::query!(
+            "DELETE FROM idempotent_response WHERE created_at < \
+             datetime('now', ?)",
+            RETENTION,
+        )
+        .execute(&mut *transaction)
+        .await?;
+        let reserved = sqlx::query!(
+            "INSERT INTO idempotent_response (route, caller, idempotency_key, \
+             request_hash) VALUES (?, ?, ?, ?) ON CONFLICT DO NOTHING",
+            route,
+            caller,
+            key,
+            request_hash,
+        )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected()
+            == 1;
+        if reserved {
+            return Ok(None);
+        }
+
+        let response = sqlx::query!(
+            "SELECT request_hash, status, body FROM idempotent_response WHERE \
+             route = ? AND caller = ? AND idempotency_key = ?",
+            route,
+            caller,
+            key,
+        )
+        .fetch_one(transaction)
+        .await?;
+
+        if response.request_hash != request_hash {
+            return Err(QueryError::InvalidInput(
+                "`Idempotency-Key` was already used for another request"
+                    .to_string(),
+            ));
+        }
+
+        match (response.status, response.body) {
+            (Some(status), Some(body)) => Ok(Some(Idempotent::Replayed((
+                Status::new(status as u16),
+                RawJson(body),
+            )))),
+            _ => Err(QueryError::Conflict(
+                "Request with this `Idempotency-Key` is still being handled"
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Stores `response` for requests repeating this key, then commits the
+    /// `transaction` the request was handled in. A server error, which a
+    /// retry may not get, rolls it back instead, releasing the key.
+    pub(crate) async fn commit<T: Serialize>(
+        &self,
+        route: &str,
+        caller: &str,
+        response: (Status, Json<ResponseBody<T, QueryError>>),
+        mut transaction: Transaction<'_, Sqlite>,
+        request_id: &RequestId,
+    ) -> Idempotent<ResponseBody<T, QueryError>> {
+        if response.0.class().is_server_error() {
+            return response.into();
+        }
+
+        let stored = match self
+            .store(route, caller, &response, &mut transaction)
+            .await
+        {
+            Ok(()) => transaction.commit().await.map_err(QueryError::from),
+            Err(error) => Err(error),
+        };
+
+        match stored {
+            Ok(()) => response.into(),
+            Err(error) => {
+                error!("Storing idempotent response failed: {error}");
+                (error.default_status(), Json((request_id, Err(error)).into()))
+                    .into()
+            },
+        }
+    }
+
+    async fn store<T: Serialize>(
+        &self,
+        route: &str,
+        caller: &str,
+        response: &(Status, Json<T>),
+        transaction: &mut SqliteConnection,
+    ) -> Result<(), QueryError> {
+        let Some(key) = &self.0 else {
+            return Ok(());
+        };
+        let (status, Json(body)) = response;
+
+        let status = status.code as i64;
+        let body = rocket::serde::json::to_string(body)
+            .expect("BUG: a response body should serialize");
+
+        sqlx::query!(
+            "UPDATE idempotent_response SET status = ?, body = ? WHERE route \
+             = ? AND caller = ? AND idempotency_key = ?",
+            status,
+            body,
+            route,
+            caller,
+            key,
+        )
+        .execute(transaction)
+        .await?;
+
+        Ok(())
+    }
+}
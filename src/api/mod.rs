@@ -1,7 +1,12 @@
 pub(crate) mod admin;
+pub(crate) mod audit;
 pub(crate) mod comparison;
+pub(crate) mod export;
 pub(crate) mod healthcheck;
+pub(crate) mod idempotency;
 pub(crate) mod options;
+pub(crate) mod project;
+pub(crate) mod ranking;
 pub(crate) mod user;
 pub(crate) mod vote;
 
@@ -107,6 +112,9 @@ pub(crate) enum QueryError {
     Sqlx(sqlx::Error),
     RowNotFound(String),
     FileServerError(String),
+    InvalidInput(String),
+    Forbidden(String),
+    Conflict(String),
 }
 
 impl From<sqlx::Error> for QueryError {
@@ -130,6 +138,9 @@ impl Display for QueryError {
             Self::Sqlx(error) => write!(f, "{}", error),
             Self::RowNotFound(message) => write!(f, "{}", message),
             Self::FileServerError(message) => write!(f, "{}", message),
+            Self::InvalidInput(message) => write!(f, "{}", message),
+            Self::Forbidden(message) => write!(f, "{}", message),
+            Self::Conflict(message) => write!(f, "{}", message),
         }
     }
 }
@@ -141,6 +152,9 @@ impl QueryError {
         match self {
             Self::RowNotFound(_) => Status::NotFound,
             Self::Sqlx(sqlx::Error::RowNotFound) => Status::NotFound,
+            Self::InvalidInput(_) => Status::UnprocessableEntity,
+            Self::Forbidden(_) => Status::Forbidden,
+            Self::Conflict(_) => Status::Conflict,
             _ => Status::InternalServerError,
         }
     }
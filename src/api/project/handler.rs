@@ -0,0 +1,74 @@
+use rocket::{
+    http::Status,
+    serde::json::Json,
+};
+use rocket_db_pools::Connection;
+
+use super::{
+    Project,
+    ProjectChange,
+};
+use crate::{
+    api::{
+        admin::{
+            Owner,
+            Viewer,
+        },
+        audit::AuditTrail,
+        QueryError,
+        RequestId,
+    },
+    response::ResponseBody,
+    DbPool,
+};
+
+#[get("/admin/project?<dirname>")]
+pub(crate) async fn get_project(
+    admin: Viewer,
+    dirname: Option<String>,
+    request_id: &RequestId,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<Project, QueryError>>) {
+    let dirname = dirname.unwrap_or("".to_string());
+    if let Err(error) = admin.check_access(&dirname) {
+        return (error.default_status(), Json((request_id, Err(error)).into()));
+    }
+
+    match super::get_project(&dirname, &mut **connection).await {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(project) => (Status::Ok, Json((request_id, Ok(project)).into())),
+    }
+}
+
+#[put(
+    "/admin/project?<dirname>",
+    format = "application/json",
+    data = "<change>"
+)]
+pub(crate) async fn set_project(
+    admin: Owner,
+    dirname: Option<String>,
+    change: Json<ProjectChange>,
+    request_id: &RequestId,
+    audit: &AuditTrail,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<Project, QueryError>>) {
+    let dirname = dirname.unwrap_or("".to_string());
+    if let Err(error) = admin.check_access(&dirname) {
+        return (error.default_status(), Json((request_id, Err(error)).into()));
+    }
+
+    match super::set_project(&dirname, change.into_inner(), &mut **connection)
+        .await
+    {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(project) => {
+            audit.add_affected_rows(1);
+            (Status::Ok, Json((request_id, Ok(project)).into()))
+        },
+    }
+}
@@ -0,0 +1,281 @@
+pub(crate) mod handler;
+
+use std::fmt::Display;
+
+use rocket::serde::json;
+use serde::{
+    Deserialize,
+    Deserializer,
+    Serialize,
+};
+use sqlx::{
+    Connection,
+    SqliteConnection,
+};
+
+use super::{
+    comparison::SelectionStrategy,
+    vote::Answer,
+    QueryError,
+};
+
+/// Per-dirname settings. Dirnames without a `project` row use the
+/// defaults.
+#[derive(Serialize)]
+pub(crate) struct Project {
+    pub(crate) dirname: String,
+    pub(crate) selection_strategy: SelectionStrategy,
+    /// Question shown to users when comparing the images of the dirname.
+    pub(crate) prompt: Option<String>,
+    /// Whether a `user` can vote again on a `comparison` they already
+    /// voted on, for test–retest studies. Otherwise repeat votes are
+    /// rejected.
+    pub(crate) allow_repeat_votes: bool,
+    /// Seconds after casting a `vote` during which its `user` can still
+    /// change or retract it, 0 to disallow.
+    pub(crate) vote_edit_window: i64,
+    /// Highest point of the scale the `comparison`s of a single image are
+    /// rated on, from 1.
+    pub(crate) rating_scale: i64,
+    /// Labels shown for the points of the rating scale, one per point, or
+    /// none.
+    pub(crate) rating_labels: RatingLabels,
+    /// Answers `user`s can give besides picking images.
+    pub(crate) answers: Answers,
+    /// Share of the `comparison`s handed out to `user`s that are gold
+    /// ones, with a known correct answer, as long as they have some left
+    /// to vote on.
+    pub(crate) gold_rate: f64,
+}
+
+/// Settings of a dirname to change, as sent to `set_project`. Omitted
+/// settings keep their current value; `prompt` is removed by sending null.
+#[derive(Default, Deserialize)]
+pub(crate) struct ProjectChange {
+    pub(crate) selection_strategy: Option<SelectionStrategy>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    pub(crate) prompt: Option<Option<String>>,
+    pub(crate) allow_repeat_votes: Option<bool>,
+    pub(crate) vote_edit_window: Option<i64>,
+    pub(crate) rating_scale: Option<i64>,
+    pub(crate) rating_labels: Option<RatingLabels>,
+    pub(crate) answers: Option<Answers>,
+    pub(crate) gold_rate: Option<f64>,
+}
+
+/// Tells a field sent as null, `Some(None)`, from an omitted one, `None`.
+fn deserialize_present<'de, D, T>(
+    deserializer: D,
+) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
+}
+
+impl Project {
+    fn apply(&mut self, change: ProjectChange) {
+        if let Some(selection_strategy) = change.selection_strategy {
+            self.selection_strategy = selection_strategy;
+        }
+        if let Some(prompt) = change.prompt {
+            self.prompt = prompt;
+        }
+        if let Some(allow_repeat_votes) = change.allow_repeat_votes {
+            self.allow_repeat_votes = allow_repeat_votes;
+        }
+        if let Some(vote_edit_window) = change.vote_edit_window {
+            self.vote_edit_window = vote_edit_window;
+        }
+        if let Some(rating_scale) = change.rating_scale {
+            self.rating_scale = rating_scale;
+        }
+        if let Some(rating_labels) = change.rating_labels {
+            self.rating_labels = rating_labels;
+        }
+        if let Some(answers) = change.answers {
+            self.answers = answers;
+        }
+        if let Some(gold_rate) = change.gold_rate {
+            self.gold_rate = gold_rate;
+        }
+    }
+}
+
+/// What `user`s are asked about the `comparison`s of a dirname, as set by
+/// its `project`.
+#[derive(Serialize)]
+pub(crate) struct Question {
+    pub(crate) prompt: Option<String>,
+    pub(crate) answers: Answers,
+    pub(crate) rating_scale: i64,
+    pub(crate) rating_labels: RatingLabels,
+}
+
+impl From<Project> for Question {
+    fn from(project: Project) -> Self {
+        Self {
+            prompt: project.prompt,
+            answers: project.answers,
+            rating_scale: project.rating_scale,
+            rating_labels: project.rating_labels,
+        }
+    }
+}
+
+/// Labels of a rating scale, stored as a JSON array.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct RatingLabels(pub(crate) Vec<String>);
+
+/// Enabled answers, stored as a JSON array.
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct Answers(pub(crate) Vec<Answer>);
+
+impl Default for Answers {
+    fn default() -> Self {
+        Self(vec![Answer::Equal, Answer::Different])
+    }
+}
+
+impl From<String> for Answers {
+    fn from(value: String) -> Self {
+        json::from_str(&value).unwrap_or_else(|error| {
+            error!(
+                "Reading `answers` {value:?} failed, using the defaults: \
+                 {error}"
+            );
+            Self::default()
+        })
+    }
+}
+
+impl Display for Answers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = json::to_string(&self.0).map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", s)
+    }
+}
+
+impl From<String> for RatingLabels {
+    fn from(value: String) -> Self {
+        json::from_str(&value).unwrap_or_else(|error| {
+            error!(
+                "Reading `rating_labels` {value:?} failed, using none: {error}"
+            );
+            Self::default()
+        })
+    }
+}
+
+impl Display for RatingLabels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = json::to_string(&self.0).map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", s)
+    }
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self {
+            dirname: "".to_string(),
+            selection_strategy: SelectionStrategy::Random,
+            prompt: None,
+            allow_repeat_votes: false,
+            vote_edit_window: 300,
+            rating_scale: 7,
+            rating_labels: RatingLabels::default(),
+            answers: Answers::default(),
+            gold_rate: 0.0,
+        }
+    }
+}
+
+pub(crate) async fn get_project(
+    dirname: &str,
+    connection: &mut SqliteConnection,
+) -> Result<Project, QueryError> {
+    let project = sqlx::query_as!(
+        Project,
+        "SELECT dirname, selection_strategy, prompt, allow_repeat_votes as \
+         \"allow_repeat_votes: bool\", vote_edit_window, rating_scale, \
+         rating_labels, answers, gold_rate FROM project WHERE dirname = ?",
+        dirname,
+    )
+    .fetch_optional(connection)
+    .await?;
+
+    Ok(project.unwrap_or_else(|| Project {
+        dirname: dirname.to_string(),
+        ..Default::default()
+    }))
+}
+
+/// Applies `change` to the settings of `dirname`, the defaults if it has
+/// none yet, and stores them.
+pub(crate) async fn set_project(
+    dirname: &str,
+    change: ProjectChange,
+    connection: &mut SqliteConnection,
+) -> Result<Project, QueryError> {
+    let mut transaction = connection.begin().await?;
+    let mut project = get_project(dirname, &mut transaction).await?;
+    project.apply(change);
+
+    if project.vote_edit_window < 0 {
+        return Err(QueryError::InvalidInput(
+            "`vote_edit_window` can't be negative".to_string(),
+        ));
+    }
+    if project.rating_scale < 2 {
+        return Err(QueryError::InvalidInput(
+            "`rating_scale` must be at least 2".to_string(),
+        ));
+    }
+    if !project.rating_labels.0.is_empty()
+        && project.rating_labels.0.len() as i64 != project.rating_scale
+    {
+        return Err(QueryError::InvalidInput(
+            "`rating_labels` must have one label per point of `rating_scale`"
+                .to_string(),
+        ));
+    }
+    if !(0.0..=1.0).contains(&project.gold_rate) {
+        return Err(QueryError::InvalidInput(
+            "`gold_rate` must be between 0 and 1".to_string(),
+        ));
+    }
+
+    let selection_strategy = project.selection_strategy.to_string();
+    let rating_labels = project.rating_labels.to_string();
+    let answers = project.answers.to_string();
+
+    let project = sqlx::query_as!(
+        Project,
+        "INSERT INTO project (dirname, selection_strategy, prompt, \
+         allow_repeat_votes, vote_edit_window, rating_scale, rating_labels, \
+         answers, gold_rate) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9) ON \
+         CONFLICT DO UPDATE SET selection_strategy = ?2, prompt = ?3, \
+         allow_repeat_votes = ?4, vote_edit_window = ?5, rating_scale = ?6, \
+         rating_labels = ?7, answers = ?8, gold_rate = ?9 RETURNING dirname, \
+         selection_strategy, prompt, allow_repeat_votes as \
+         \"allow_repeat_votes: bool\", vote_edit_window, rating_scale, \
+         rating_labels, answers, gold_rate",
+        project.dirname,
+        selection_strategy,
+        project.prompt,
+        project.allow_repeat_votes,
+        project.vote_edit_window,
+        project.rating_scale,
+        rating_labels,
+        answers,
+        project.gold_rate,
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+
+    Ok(project)
+}
@@ -0,0 +1,57 @@
+use rocket::{
+    http::Status,
+    serde::json::Json,
+};
+use rocket_db_pools::Connection;
+
+use super::Score;
+use crate::{
+    api::{
+        admin::Viewer,
+        QueryError,
+        RequestId,
+    },
+    response::ResponseBody,
+    DbPool,
+};
+
+#[get("/comparison/dirnames/<dirname>/ranking")]
+pub(crate) async fn get_ranking<'r>(
+    admin: Viewer,
+    dirname: &str,
+    request_id: &RequestId,
+    connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<Vec<Score<'r>>, QueryError>>) {
+    rank_dirname(admin, dirname, request_id, connection).await
+}
+
+/// Same as [`get_ranking`], with the dirname in the query, so the root
+/// dirname (the default) and nested ones need no percent-encoding.
+#[get("/comparison/ranking?<dirname>")]
+pub(crate) async fn get_ranking_of_dirname<'r>(
+    admin: Viewer,
+    dirname: Option<String>,
+    request_id: &RequestId,
+    connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<Vec<Score<'r>>, QueryError>>) {
+    let dirname = dirname.unwrap_or("".to_string());
+    rank_dirname(admin, &dirname, request_id, connection).await
+}
+
+async fn rank_dirname<'r>(
+    admin: Viewer,
+    dirname: &str,
+    request_id: &RequestId,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<Vec<Score<'r>>, QueryError>>) {
+    if let Err(error) = admin.check_access(dirname) {
+        return (error.default_status(), Json((request_id, Err(error)).into()));
+    }
+
+    match super::get_ranking(dirname, &mut **connection).await {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(scores) => (Status::Ok, Json((request_id, Ok(scores)).into())),
+    }
+}
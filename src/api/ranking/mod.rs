@@ -0,0 +1,604 @@
+pub(crate) mod handler;
+
+use std::collections::{
+    btree_map::Entry,
+    BTreeMap,
+};
+
+use rocket::http::uri::Origin;
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use super::{
+    vote::VoteValue,
+    QueryError,
+    SqliteArray,
+    SqliteUuid,
+};
+
+/// Strength of the Gaussian prior (ridge penalty) on the latent scores.
+/// It keeps the scores finite when an image wins or loses every
+/// comparison it appears in, and gives images without votes a score of 0.
+const RIDGE_PENALTY: f64 = 0.01;
+const MAX_ITERATIONS: usize = 100;
+const TOLERANCE: f64 = 1e-10;
+/// Confidence a vote without one is taken to have. A vote weighs its
+/// confidence divided by this, so unrated votes count as before.
+const NEUTRAL_CONFIDENCE: f64 = 3.0;
+
+#[derive(Serialize)]
+pub(crate) struct Score<'a> {
+    pub(crate) image: Origin<'a>,
+    pub(crate) score: f64,
+    pub(crate) standard_error: f64,
+    /// Mean of the ratings given to the image on its own, on the scale of
+    /// the `project`, to anchor the scores with.
+    pub(crate) mean_rating: Option<f64>,
+    pub(crate) ratings: i64,
+}
+
+/// Outcome of a single vote between two images, identified by their
+/// index in the list of images being ranked, weighted by the confidence
+/// of the vote.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Contest {
+    pub(crate) winner: usize,
+    pub(crate) loser: usize,
+    pub(crate) tie: bool,
+    pub(crate) weight: f64,
+}
+
+pub(crate) async fn get_ranking<'r>(
+    dirname: &str,
+    connection: &mut SqliteConnection,
+) -> Result<Vec<Score<'r>>, QueryError> {
+    let (images, votes) = get_contests(dirname, connection).await?;
+    let contests: Vec<Contest> =
+        votes.into_iter().map(|(_, contest)| contest).collect();
+    let fit = fit_bradley_terry(images.len(), &contests);
+    let ratings = get_ratings(dirname, connection).await?;
+
+    let mut scores: Vec<Score> = images
+        .into_iter()
+        .zip(fit)
+        .map(|(image, (score, standard_error))| {
+            let (mean_rating, ratings) = ratings
+                .get(image.path().as_str())
+                .map_or((None, 0), |&(mean, count)| (Some(mean), count));
+            Score {
+                image,
+                score,
+                standard_error,
+                mean_rating,
+                ratings,
+            }
+        })
+        .collect();
+
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    Ok(scores)
+}
+
+/// Returns every image in the `comparison`s of `dirname` together with
+/// the `Contest`s decided by the votes cast on them, paired with the id of
+/// the `user` who voted. `different` votes carry no ordering information
+/// and are skipped. Votes on more than two images decide a `Contest` for
+/// each pair of images they order. Retired and gold `comparison`s are left
+/// out.
+pub(crate) async fn get_contests<'r>(
+    dirname: &str,
+    connection: &mut SqliteConnection,
+) -> Result<(Vec<Origin<'r>>, Vec<(Uuid, Contest)>), QueryError> {
+    let comparisons = sqlx::query_as!(
+        ComparisonImages,
+        "SELECT images FROM comparison WHERE dirname = ? AND retired_at IS \
+         NULL AND expected_vote_value IS NULL",
+        dirname,
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    if comparisons.is_empty() {
+        return Err(QueryError::RowNotFound(
+            "No `comparison`s found for requested `dirname`".to_string(),
+        ));
+    }
+
+    let mut indexes: BTreeMap<String, usize> = BTreeMap::new();
+    let mut images = Vec::new();
+    for comparison in comparisons {
+        for image in comparison.images.iter() {
+            let path = image.path().to_string();
+            if let Entry::Vacant(entry) = indexes.entry(path) {
+                entry.insert(images.len());
+                images.push(image.clone());
+            }
+        }
+    }
+
+    let votes = sqlx::query_as!(
+        ComparisonVote,
+        "SELECT comparison.images, vote.user_id, vote.vote_value, \
+         vote.confidence FROM vote INNER JOIN comparison ON \
+         vote.comparison_id = comparison.id WHERE comparison.dirname = ? AND \
+         comparison.retired_at IS NULL AND comparison.expected_vote_value IS \
+         NULL AND vote.retracted_at IS NULL",
+        dirname,
+    )
+    .fetch_all(connection)
+    .await?;
+
+    let contests = votes
+        .iter()
+        .flat_map(|vote| {
+            vote.to_contests(&indexes)
+                .into_iter()
+                .map(|contest| (*vote.user_id, contest))
+        })
+        .collect();
+
+    Ok((images, contests))
+}
+
+/// Returns the mean and number of the ratings of each image of `dirname`
+/// rated on its own, by path, leaving out retired and gold `comparison`s
+/// like [`get_contests`].
+async fn get_ratings(
+    dirname: &str,
+    connection: &mut SqliteConnection,
+) -> Result<BTreeMap<String, (f64, i64)>, QueryError> {
+    let ratings = sqlx::query_as!(
+        ImageRatings,
+        "SELECT comparison.images, AVG(CAST(vote.vote_value AS REAL)) as \
+         \"mean_rating!: f64\", COUNT(*) as \"ratings: i64\" FROM vote INNER \
+         JOIN comparison ON vote.comparison_id = comparison.id WHERE \
+         comparison.dirname = ? AND instr(comparison.images, '///') = 0 AND \
+         comparison.retired_at IS NULL AND comparison.expected_vote_value IS \
+         NULL AND vote.retracted_at IS NULL GROUP BY comparison.images",
+        dirname,
+    )
+    .fetch_all(connection)
+    .await?;
+
+    Ok(ratings
+        .into_iter()
+        .filter_map(|rating| {
+            let image = rating.images.first()?.path().to_string();
+            Some((image, (rating.mean_rating, rating.ratings)))
+        })
+        .collect())
+}
+
+struct ComparisonImages<'a> {
+    images: SqliteArray<'a>,
+}
+
+struct ImageRatings<'a> {
+    images: SqliteArray<'a>,
+    mean_rating: f64,
+    ratings: i64,
+}
+
+struct ComparisonVote<'a> {
+    images: SqliteArray<'a>,
+    user_id: SqliteUuid,
+    vote_value: VoteValue,
+    confidence: Option<i64>,
+}
+
+impl<'a> ComparisonVote<'a> {
+    /// Breaks the vote into a `Contest` for every two images it orders,
+    /// each image beating those of the tiers below its own. An `equal`
+    /// vote ties every two images instead. The contests of a vote on `k`
+    /// images are not independent, so each only weighs `1 / (k - 1)` of
+    /// the vote: a ranking counts as `k / 2` votes on a pair rather than
+    /// `k (k - 1) / 2`.
+    fn to_contests(&self, indexes: &BTreeMap<String, usize>) -> Vec<Contest> {
+        let paths: Vec<&str> = self
+            .images
+            .iter()
+            .map(|image| image.path().as_str())
+            .collect();
+        let Some(items) = paths
+            .iter()
+            .map(|path| indexes.get(*path).copied())
+            .collect::<Option<Vec<usize>>>()
+        else {
+            return Vec::new();
+        };
+        let Some(tiers) = self.vote_value.tiers(&paths) else {
+            return Vec::new();
+        };
+        let weight = self
+            .confidence
+            .map_or(1.0, |confidence| confidence as f64 / NEUTRAL_CONFIDENCE)
+            / (items.len().max(2) - 1) as f64;
+
+        let mut contests = Vec::new();
+        if let (VoteValue::Equal, [tier]) = (&self.vote_value, tiers.as_slice())
+        {
+            for (i, &a) in tier.iter().enumerate() {
+                for &b in &tier[(i + 1)..] {
+                    contests.push(Contest {
+                        winner: items[a],
+                        loser: items[b],
+                        tie: true,
+                        weight,
+                    });
+                }
+            }
+
+            return contests;
+        }
+
+        for (i, winners) in tiers.iter().enumerate() {
+            for &loser in tiers[(i + 1)..].iter().flatten() {
+                for &winner in winners {
+                    contests.push(Contest {
+                        winner: items[winner],
+                        loser: items[loser],
+                        tie: false,
+                        weight,
+                    });
+                }
+            }
+        }
+
+        contests
+    }
+}
+
+/// Fits a Bradley–Terry model by Newton's method and returns, for each of
+/// the `items`, its latent score (log-strength) and standard error. Ties
+/// count as half a win for each side, and each contest counts as much as
+/// its weight. Standard errors come from the inverse of the (penalized)
+/// observed information at the optimum.
+pub(crate) fn fit_bradley_terry(
+    items: usize,
+    contests: &[Contest],
+) -> Vec<(f64, f64)> {
+    let mut scores = vec![0.0; items];
+    let mut covariance = vec![vec![0.0; items]; items];
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut gradient: Vec<f64> =
+            scores.iter().map(|score| -RIDGE_PENALTY * score).collect();
+        let mut information = vec![vec![0.0; items]; items];
+        for (i, row) in information.iter_mut().enumerate() {
+            row[i] = RIDGE_PENALTY;
+        }
+
+        for contest in contests {
+            let (i, j) = (contest.winner, contest.loser);
+            let p = logistic(scores[i] - scores[j]);
+            let outcome = if contest.tie { 0.5 } else { 1.0 };
+            let weight = contest.weight * p * (1.0 - p);
+
+            gradient[i] += contest.weight * (outcome - p);
+            gradient[j] -= contest.weight * (outcome - p);
+            information[i][i] += weight;
+            information[j][j] += weight;
+            information[i][j] -= weight;
+            information[j][i] -= weight;
+        }
+
+        covariance = invert(information);
+
+        let mut max_step: f64 = 0.0;
+        for (score, row) in scores.iter_mut().zip(&covariance) {
+            let step: f64 = row.iter().zip(&gradient).map(|(c, g)| c * g).sum();
+            *score += step;
+            max_step = max_step.max(step.abs());
+        }
+
+        if max_step < TOLERANCE {
+            break;
+        }
+    }
+
+    scores
+        .into_iter()
+        .enumerate()
+        .map(|(i, score)| (score, covariance[i][i].sqrt()))
+        .collect()
+}
+
+/// How much a single vote agrees with a fitted model, from 0 to 1. For a
+/// decisive vote this is the probability the model gives to the chosen
+/// image winning; for a tie it is 1 when the model considers both images
+/// equally strong, decreasing as their scores move apart.
+pub(crate) fn agreement(scores: &[(f64, f64)], contest: &Contest) -> f64 {
+    let p = logistic(scores[contest.winner].0 - scores[contest.loser].0);
+
+    if contest.tie {
+        1.0 - (2.0 * p - 1.0).abs()
+    } else {
+        p
+    }
+}
+
+/// Expected reduction in the variance of the score difference between
+/// items `a` and `b` from one more vote on them. The difference is treated
+/// as Gaussian with the variance given by the standard errors, and a vote
+/// brings Fisher information `p * (1 - p)` about it, so pairs whose scores
+/// are close and uncertain gain the most.
+pub(crate) fn expected_information(
+    scores: &[(f64, f64)],
+    a: usize,
+    b: usize,
+) -> f64 {
+    let (score_a, standard_error_a) = scores[a];
+    let (score_b, standard_error_b) = scores[b];
+    let p = logistic(score_a - score_b);
+    let information = p * (1.0 - p);
+    let variance = standard_error_a.powi(2) + standard_error_b.powi(2);
+
+    variance.powi(2) * information / (1.0 + variance * information)
+}
+
+fn logistic(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Inverts a symmetric positive definite matrix by Gauss-Jordan
+/// elimination with partial pivoting.
+fn invert(mut matrix: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|&a, &b| {
+                matrix[a][column].abs().total_cmp(&matrix[b][column].abs())
+            })
+            .expect("BUG: range should not be empty");
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+
+        let divisor = matrix[column][column];
+        for j in 0..n {
+            matrix[column][j] /= divisor;
+            inverse[column][j] /= divisor;
+        }
+
+        for row in 0..n {
+            if row == column {
+                continue;
+            }
+            let factor = matrix[row][column];
+            if factor == 0.0 {
+                continue;
+            }
+            for j in 0..n {
+                matrix[row][j] -= factor * matrix[column][j];
+                inverse[row][j] -= factor * inverse[column][j];
+            }
+        }
+    }
+
+    inverse
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{
+        ComparisonVote,
+        Contest,
+    };
+    use crate::api::vote::VoteValue;
+
+    fn vote(images: &str, vote_value: &str) -> ComparisonVote<'static> {
+        ComparisonVote {
+            images: images.to_string().into(),
+            user_id: vec![0; 16].into(),
+            vote_value: vote_value.to_string().into(),
+            confidence: None,
+        }
+    }
+
+    fn indexes(vote: &ComparisonVote) -> BTreeMap<String, usize> {
+        vote.images
+            .iter()
+            .enumerate()
+            .map(|(index, image)| (image.path().to_string(), index))
+            .collect()
+    }
+
+    fn win(winner: usize, loser: usize) -> Contest {
+        Contest {
+            winner,
+            loser,
+            tie: false,
+            weight: 1.0,
+        }
+    }
+
+    fn weighted(contests: Vec<Contest>, weight: f64) -> Vec<Contest> {
+        contests
+            .into_iter()
+            .map(|contest| Contest { weight, ..contest })
+            .collect()
+    }
+
+    #[test]
+    fn ranking_vote_decides_every_pair() {
+        let vote = vote(
+            "a.png///b.png///c.png",
+            r#"["/static/images/c.png","/static/images/a.png",
+                "/static/images/b.png"]"#,
+        );
+        assert!(matches!(vote.vote_value, VoteValue::Ranking(_)));
+
+        let contests = vote.to_contests(&indexes(&vote));
+
+        assert_eq!(
+            contests,
+            weighted(vec![win(2, 0), win(2, 1), win(0, 1)], 0.5)
+        );
+    }
+
+    #[test]
+    fn best_worst_vote_leaves_middle_images_unordered() {
+        let vote = vote(
+            "a.png///b.png///c.png///d.png",
+            r#"{"best":"/static/images/b.png","worst":"/static/images/d.png"}"#,
+        );
+
+        let contests = vote.to_contests(&indexes(&vote));
+
+        assert_eq!(
+            contests,
+            weighted(
+                vec![win(1, 0), win(1, 2), win(1, 3), win(0, 3), win(2, 3)],
+                1.0 / 3.0
+            )
+        );
+    }
+
+    #[test]
+    fn fit_bradley_terry_orders_items_by_wins() {
+        let contests = vec![
+            win(0, 1),
+            win(0, 1),
+            win(1, 0),
+            win(1, 2),
+            win(1, 2),
+            win(2, 1),
+            win(0, 2),
+            win(0, 2),
+            win(0, 2),
+        ];
+
+        let scores = super::fit_bradley_terry(3, &contests);
+
+        assert!(scores[0].0 > scores[1].0);
+        assert!(scores[1].0 > scores[2].0);
+    }
+
+    #[test]
+    fn fit_bradley_terry_scores_sum_to_zero() {
+        let contests = vec![win(0, 1), win(1, 2), win(0, 2), win(2, 3)];
+
+        let scores = super::fit_bradley_terry(4, &contests);
+        let sum: f64 = scores.iter().map(|(score, _)| score).sum();
+
+        assert!(sum.abs() < 1e-9);
+    }
+
+    #[test]
+    fn fit_bradley_terry_ties_give_equal_scores() {
+        let tie = Contest {
+            winner: 0,
+            loser: 1,
+            tie: true,
+            weight: 1.0,
+        };
+        let contests = vec![tie, tie, tie];
+
+        let scores = super::fit_bradley_terry(2, &contests);
+
+        assert!((scores[0].0 - scores[1].0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fit_bradley_terry_matches_known_probability() {
+        // 3 wins to 1 means a probability of 0.75 for the first item,
+        // so the score difference should be close to ln(3)
+        let contests = vec![win(0, 1), win(0, 1), win(0, 1), win(1, 0)];
+
+        let scores = super::fit_bradley_terry(2, &contests);
+        let difference = scores[0].0 - scores[1].0;
+
+        assert!((difference - 3.0_f64.ln()).abs() < 0.05);
+    }
+
+    #[test]
+    fn fit_bradley_terry_favours_confident_votes() {
+        let confident = Contest {
+            weight: 5.0 / 3.0,
+            ..win(0, 1)
+        };
+        let coin_flip = Contest {
+            weight: 1.0 / 3.0,
+            ..win(1, 0)
+        };
+
+        let scores = super::fit_bradley_terry(2, &[confident, coin_flip]);
+
+        assert!(scores[0].0 > scores[1].0);
+    }
+
+    #[test]
+    fn fit_bradley_terry_standard_error_shrinks_with_more_votes() {
+        let few = vec![win(0, 1), win(1, 0)];
+        let many: Vec<Contest> = few.iter().cycle().take(40).cloned().collect();
+
+        let few_scores = super::fit_bradley_terry(2, &few);
+        let many_scores = super::fit_bradley_terry(2, &many);
+
+        assert!(many_scores[0].1 < few_scores[0].1);
+    }
+
+    #[test]
+    fn agreement_is_higher_for_votes_following_the_model() {
+        let scores = vec![(1.0, 0.1), (-1.0, 0.1)];
+
+        let with_model = super::agreement(&scores, &win(0, 1));
+        let against_model = super::agreement(&scores, &win(1, 0));
+
+        assert!(with_model > 0.5);
+        assert!((with_model + against_model - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn agreement_of_tie_is_maximal_for_equal_scores() {
+        let tie = Contest {
+            winner: 0,
+            loser: 1,
+            tie: true,
+            weight: 1.0,
+        };
+
+        let equal = super::agreement(&[(0.5, 0.1), (0.5, 0.1)], &tie);
+        let apart = super::agreement(&[(2.0, 0.1), (-2.0, 0.1)], &tie);
+
+        assert!((equal - 1.0).abs() < 1e-9);
+        assert!(apart < equal);
+    }
+
+    #[test]
+    fn expected_information_prefers_close_scores() {
+        let scores = vec![(0.1, 0.5), (0.0, 0.5), (3.0, 0.5)];
+
+        let close = super::expected_information(&scores, 0, 1);
+        let apart = super::expected_information(&scores, 1, 2);
+
+        assert!(close > apart);
+    }
+
+    #[test]
+    fn expected_information_prefers_uncertain_scores() {
+        let scores = vec![(0.0, 0.2), (0.0, 0.2), (0.0, 2.0), (0.0, 2.0)];
+
+        let certain = super::expected_information(&scores, 0, 1);
+        let uncertain = super::expected_information(&scores, 2, 3);
+
+        assert!(uncertain > certain);
+    }
+
+    #[test]
+    fn fit_bradley_terry_without_votes_returns_zero_scores() {
+        let scores = super::fit_bradley_terry(3, &[]);
+
+        for (score, standard_error) in scores {
+            assert_eq!(score, 0.0);
+            assert!(standard_error.is_finite());
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use rocket::{
     http::Status,
     serde::{
@@ -6,10 +8,15 @@ use rocket::{
     },
 };
 use rocket_db_pools::Connection;
+use sqlx::Connection as _;
 
 use super::User;
 use crate::{
     api::{
+        idempotency::{
+            IdempotencyKey,
+            Idempotent,
+        },
         QueryError,
         RequestId,
     },
@@ -31,15 +38,58 @@ pub(crate) async fn get_user(
     }
 }
 
+/// A key is only honored for requests coming from the same address, since
+/// a new `user` has nothing else to tell clients apart, and must be a UUID
+/// so clients sharing an address don't reuse each other's keys.
 #[post("/user")]
 pub(crate) async fn generate_user(
+    idempotency_key: IdempotencyKey,
+    ip_addr: Option<IpAddr>,
     request_id: &RequestId,
     mut connection: Connection<DbPool>,
-) -> (Status, Json<ResponseBody<User, QueryError>>) {
-    match super::generate_user(&mut **connection).await {
+) -> Idempotent<ResponseBody<User, QueryError>> {
+    if let Err(error) = idempotency_key.check_uuid() {
+        return (error.default_status(), Json((request_id, Err(error)).into()))
+            .into();
+    }
+    let caller = ip_addr
+        .map(|ip| ip.to_canonical().to_string())
+        .unwrap_or_default();
+
+    let mut transaction = match connection.begin().await {
+        Err(error) => {
+            let error = QueryError::from(error);
+            return (
+                error.default_status(),
+                Json((request_id, Err(error)).into()),
+            )
+                .into();
+        },
+        Ok(transaction) => transaction,
+    };
+    match idempotency_key
+        .reserve("/user", &caller, "", &mut transaction)
+        .await
+    {
+        Err(error) => {
+            return (
+                error.default_status(),
+                Json((request_id, Err(error)).into()),
+            )
+                .into()
+        },
+        Ok(Some(response)) => return response,
+        Ok(None) => (),
+    }
+
+    let response = match super::generate_user(&mut transaction).await {
         Err(error) => {
             (error.default_status(), Json((request_id, Err(error)).into()))
         },
         Ok(user) => (Status::Created, Json((request_id, Ok(user)).into())),
-    }
+    };
+
+    idempotency_key
+        .commit("/user", &caller, response, transaction, request_id)
+        .await
 }
@@ -1,10 +1,35 @@
 pub(crate) mod handler;
 
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
+    time::Duration,
+};
+
+use rocket::tokio::{
+    sync::Notify,
+    time::sleep,
+};
 use serde::Serialize;
-use sqlx::SqliteConnection;
+use sqlx::{
+    SqliteConnection,
+    SqlitePool,
+};
 use uuid::Uuid;
 
 use super::{
+    ranking::{
+        agreement,
+        fit_bradley_terry,
+        get_contests,
+        Contest,
+    },
     QueryError,
     SqliteUuid,
 };
@@ -14,6 +39,9 @@ pub(crate) struct User {
     pub(crate) id: SqliteUuid,
     pub(crate) votes: i64,
     pub(crate) average_lambda: f64,
+    /// Share of the gold `comparison`s the `user` voted on that they gave
+    /// the expected answer to, or null if they voted on none.
+    pub(crate) reliability: Option<f64>,
 }
 
 pub(crate) async fn get_user(
@@ -23,7 +51,10 @@ pub(crate) async fn get_user(
     sqlx::query_as!(
         User,
         "SELECT user.*, (SELECT COUNT(vote.id) FROM vote WHERE vote.user_id = \
-         user.id) as `votes!: i64` FROM user WHERE user.id = ?",
+         user.id AND vote.retracted_at IS NULL) as `votes!: i64`, (SELECT \
+         AVG(vote.gold_passed) FROM vote WHERE vote.user_id = user.id AND \
+         vote.gold_passed IS NOT NULL AND vote.retracted_at IS NULL) as \
+         `reliability?: f64` FROM user WHERE user.id = ?",
         id
     )
     .fetch_one(connection)
@@ -46,7 +77,8 @@ pub(crate) async fn generate_user(
             Err(QueryError::RowNotFound(_)) => {
                 return sqlx::query_as!(
                     User,
-                    "INSERT INTO user (id) VALUES (?) RETURNING *, 0 as votes",
+                    "INSERT INTO user (id) VALUES (?) RETURNING *, 0 as \
+                     votes, NULL as `reliability?: f64`",
                     id
                 )
                 .fetch_one(connection)
@@ -57,3 +89,129 @@ pub(crate) async fn generate_user(
         }
     }
 }
+
+/// How long a refresh waits once a `user` is marked, so a burst of votes
+/// is refitted at once.
+const REFRESH_DELAY: Duration = Duration::from_secs(1);
+
+/// `user`s whose `average_lambda` is out of date because their votes
+/// changed. It is recomputed by [`StaleLambdas::refresh`] in the
+/// background, so requests changing votes never wait for the fits.
+#[derive(Clone, Default)]
+pub(crate) struct StaleLambdas(Arc<StaleUsers>);
+
+#[derive(Default)]
+struct StaleUsers {
+    ids: Mutex<BTreeSet<Uuid>>,
+    marked: Notify,
+}
+
+impl StaleLambdas {
+    /// Marks the `user`s for the next refresh.
+    pub(crate) fn mark(&self, ids: impl IntoIterator<Item = Uuid>) {
+        self.0
+            .ids
+            .lock()
+            .expect("BUG: lock should not be poisoned")
+            .extend(ids);
+        self.0.marked.notify_one();
+    }
+
+    /// Recomputes `average_lambda` of the marked `user`s whenever some
+    /// are marked, on a connection of its own from the `pool`. A failure
+    /// is only logged: the next vote of a `user` marks them again.
+    pub(crate) async fn refresh(self, pool: SqlitePool) {
+        loop {
+            self.0.marked.notified().await;
+            sleep(REFRESH_DELAY).await;
+
+            let ids = std::mem::take(
+                &mut *self
+                    .0
+                    .ids
+                    .lock()
+                    .expect("BUG: lock should not be poisoned"),
+            );
+            if ids.is_empty() {
+                continue;
+            }
+
+            let result = match pool.acquire().await {
+                Ok(mut connection) => {
+                    update_average_lambdas(&ids, &mut connection).await
+                },
+                Err(error) => Err(error.into()),
+            };
+            if let Err(error) = result {
+                error!(
+                    "Updating `average_lambda` of {} users failed: {error}",
+                    ids.len()
+                );
+            }
+        }
+    }
+}
+
+/// Recomputes `average_lambda` for each of the `user`s: the mean agreement
+/// (see [`agreement`]) of the user's votes with the Bradley–Terry model
+/// fitted on all votes of each dirname the user has voted in, each contest
+/// weighted as in the fit. Each dirname is fitted once for all of them.
+/// Users without rankable votes (only `different`) get a value of 0.0.
+pub(crate) async fn update_average_lambdas(
+    ids: &BTreeSet<Uuid>,
+    connection: &mut SqliteConnection,
+) -> Result<(), QueryError> {
+    let mut dirnames = BTreeSet::new();
+    for id in ids {
+        dirnames.extend(
+            sqlx::query_scalar!(
+                "SELECT DISTINCT comparison.dirname FROM vote INNER JOIN \
+                 comparison ON vote.comparison_id = comparison.id WHERE \
+                 vote.user_id = ? AND vote.retracted_at IS NULL",
+                id
+            )
+            .fetch_all(&mut *connection)
+            .await?,
+        );
+    }
+
+    let mut agreements: BTreeMap<Uuid, Vec<(f64, f64)>> =
+        ids.iter().map(|id| (*id, Vec::new())).collect();
+    for dirname in dirnames {
+        let (images, votes) = get_contests(&dirname, connection).await?;
+        let contests: Vec<Contest> =
+            votes.iter().map(|(_, contest)| *contest).collect();
+        let scores = fit_bradley_terry(images.len(), &contests);
+
+        for (user_id, contest) in &votes {
+            if let Some(agreements) = agreements.get_mut(user_id) {
+                agreements.push((agreement(&scores, contest), contest.weight));
+            }
+        }
+    }
+
+    for (id, agreements) in agreements {
+        let total_weight: f64 =
+            agreements.iter().map(|(_, weight)| weight).sum();
+        let average_lambda = match agreements.len() {
+            0 => 0.0,
+            _ => {
+                agreements
+                    .iter()
+                    .map(|(agreement, weight)| agreement * weight)
+                    .sum::<f64>()
+                    / total_weight
+            },
+        };
+
+        sqlx::query!(
+            "UPDATE user SET average_lambda = ? WHERE id = ?",
+            average_lambda,
+            id
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    Ok(())
+}
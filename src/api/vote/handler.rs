@@ -2,13 +2,29 @@ use std::net::IpAddr;
 
 use rocket::{
     http::Status,
-    serde::json::Json,
+    serde::json::{
+        self,
+        Json,
+    },
+    State,
 };
 use rocket_db_pools::Connection;
+use sqlx::Connection as _;
+use uuid::Uuid;
 
-use super::Vote;
+use super::{
+    BatchedVote,
+    BatchedVoteResult,
+    Vote,
+    VoteChange,
+};
 use crate::{
     api::{
+        idempotency::{
+            IdempotencyKey,
+            Idempotent,
+        },
+        user::StaleLambdas,
         QueryError,
         RequestId,
     },
@@ -20,13 +36,47 @@ use crate::{
 pub(crate) async fn vote(
     mut vote: Json<Vote>,
     ip_addr: Option<IpAddr>,
+    idempotency_key: IdempotencyKey,
     request_id: &RequestId,
+    stale_lambdas: &State<StaleLambdas>,
     mut connection: Connection<DbPool>,
-) -> (Status, Json<ResponseBody<Vote, QueryError>>) {
+) -> Idempotent<ResponseBody<Vote, QueryError>> {
+    // a key is only honored for the `user` who sent it, with the same vote
+    let caller = vote.user_id.to_string();
+    let request =
+        json::to_string(&*vote).expect("BUG: a vote should serialize");
+
+    let mut transaction = match connection.begin().await {
+        Err(error) => {
+            let error = QueryError::from(error);
+            return (
+                error.default_status(),
+                Json((request_id, Err(error)).into()),
+            )
+                .into();
+        },
+        Ok(transaction) => transaction,
+    };
+    match idempotency_key
+        .reserve("/vote", &caller, &request, &mut transaction)
+        .await
+    {
+        Err(error) => {
+            return (
+                error.default_status(),
+                Json((request_id, Err(error)).into()),
+            )
+                .into()
+        },
+        Ok(Some(response)) => return response,
+        Ok(None) => (),
+    }
+
     vote.ip_addr = ip_addr.map(|ip| ip.to_canonical().to_string());
-    let result = super::create_vote(&vote, &mut **connection).await;
+    let result = super::create_vote(&vote, &mut transaction).await;
+    let voted = result.is_ok();
 
-    match result {
+    let response = match result {
         Err(QueryError::RowNotFound(message)) => (
             Status::UnprocessableEntity,
             Json((request_id, Err(QueryError::RowNotFound(message))).into()),
@@ -35,5 +85,75 @@ pub(crate) async fn vote(
             (error.default_status(), Json((request_id, Err(error)).into()))
         },
         Ok(vote) => (Status::Created, Json((request_id, Ok(vote)).into())),
+    };
+
+    let response = idempotency_key
+        .commit("/vote", &caller, response, transaction, request_id)
+        .await;
+    if voted {
+        stale_lambdas.mark([*vote.user_id]);
+    }
+
+    response
+}
+
+#[post("/votes", format = "application/json", data = "<votes>")]
+pub(crate) async fn votes(
+    votes: Json<Vec<BatchedVote>>,
+    ip_addr: Option<IpAddr>,
+    request_id: &RequestId,
+    stale_lambdas: &State<StaleLambdas>,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<Vec<BatchedVoteResult>, QueryError>>) {
+    let ip_addr = ip_addr.map(|ip| ip.to_canonical().to_string());
+    let result = super::create_votes(
+        votes.into_inner(),
+        ip_addr,
+        stale_lambdas,
+        &mut **connection,
+    )
+    .await;
+
+    match result {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(results) => (Status::Ok, Json((request_id, Ok(results)).into())),
+    }
+}
+
+#[put("/vote/<id>", format = "application/json", data = "<change>")]
+pub(crate) async fn change_vote(
+    id: i64,
+    change: Json<VoteChange>,
+    request_id: &RequestId,
+    stale_lambdas: &State<StaleLambdas>,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<Vote, QueryError>>) {
+    match super::change_vote(id, &change, stale_lambdas, &mut **connection)
+        .await
+    {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(vote) => (Status::Ok, Json((request_id, Ok(vote)).into())),
+    }
+}
+
+#[delete("/vote/<id>?<user_id>")]
+pub(crate) async fn retract_vote(
+    id: i64,
+    user_id: Uuid,
+    request_id: &RequestId,
+    stale_lambdas: &State<StaleLambdas>,
+    mut connection: Connection<DbPool>,
+) -> (Status, Json<ResponseBody<Vote, QueryError>>) {
+    match super::retract_vote(id, user_id, stale_lambdas, &mut **connection)
+        .await
+    {
+        Err(error) => {
+            (error.default_status(), Json((request_id, Err(error)).into()))
+        },
+        Ok(vote) => (Status::Ok, Json((request_id, Ok(vote)).into())),
     }
 }
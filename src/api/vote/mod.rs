@@ -1,19 +1,31 @@
 pub(crate) mod handler;
 
-use std::fmt::Display;
+use std::{
+    collections::BTreeSet,
+    fmt::Display,
+};
 
 use chrono::{
     DateTime,
+    Duration,
     Utc,
 };
+use rocket::{
+    http::Status,
+    serde::json,
+};
 use serde::{
     Deserialize,
     Serialize,
 };
-use sqlx::SqliteConnection;
+use sqlx::{
+    Connection,
+    SqliteConnection,
+};
 use uuid::Uuid;
 
 use super::{
+    user::StaleLambdas,
     QueryError,
     SqliteArray,
     SqliteUuid,
@@ -30,57 +42,625 @@ pub(crate) struct Vote {
     pub(crate) created_at: DateTime<Utc>,
     #[serde(skip_deserializing)]
     pub(crate) ip_addr: Option<String>,
+    /// How many votes the `user` cast on the `comparison` before this
+    /// one, only ever above 0 in dirnames allowing repeat votes.
+    #[serde(skip_deserializing)]
+    pub(crate) repetition: i64,
+    /// When the `user` took the vote back. Retracted votes are kept, but
+    /// count nowhere anymore.
+    #[serde(skip_deserializing)]
+    pub(crate) retracted_at: Option<DateTime<Utc>>,
+    /// Milliseconds between handing the `comparison` out to the `user` and
+    /// their vote, if it was handed out through `get_comparison_for_user`.
+    #[serde(skip_deserializing)]
+    pub(crate) response_time_ms: Option<i64>,
+    /// Size of the viewport of the `user` in CSS pixels, as reported by
+    /// the client.
+    pub(crate) viewport_width: Option<i64>,
+    pub(crate) viewport_height: Option<i64>,
+    pub(crate) device_pixel_ratio: Option<f64>,
+    /// How sure the `user` was, from 1 (coin flip) to 5 (clearly), used
+    /// to weight the vote in the ranking.
+    pub(crate) confidence: Option<i64>,
+    /// Key the client identifies the vote with, so submitting it again
+    /// returns the stored vote instead of a new one.
+    #[serde(skip_deserializing)]
+    pub(crate) idempotency_key: Option<String>,
+    /// When the client recorded the vote, for votes queued while offline.
+    #[serde(skip_deserializing)]
+    pub(crate) client_created_at: Option<DateTime<Utc>>,
+}
+
+/// A `vote` queued by a client, as submitted to `create_votes`.
+#[derive(Deserialize)]
+pub(crate) struct BatchedVote {
+    pub(crate) idempotency_key: String,
+    pub(crate) client_created_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub(crate) vote: Vote,
 }
 
+/// Outcome of one `BatchedVote`, with the status its own request would
+/// have had.
+#[derive(Serialize)]
+pub(crate) struct BatchedVoteResult {
+    pub(crate) idempotency_key: String,
+    pub(crate) status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) vote: Option<Vote>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<QueryError>,
+}
+
+/// A new `vote_value` for a `vote`, sent by the `user` who cast it.
+#[derive(Deserialize)]
+pub(crate) struct VoteChange {
+    pub(crate) user_id: SqliteUuid,
+    pub(crate) vote_value: VoteValue,
+}
+
+/// What a `user` answered about the images of a `comparison`. Images
+/// are named by their path. Rankings and best-worst selections are meant
+/// for `comparison`s of more than two images, and are stored as JSON,
+/// while a `comparison` of a single image can only be rated.
 #[derive(Serialize, Deserialize)]
 pub(crate) enum VoteValue {
     #[serde(rename = "equal")]
     Equal,
     #[serde(rename = "different")]
     Different,
+    #[serde(rename = "cannot_tell")]
+    CannotTell,
     #[serde(untagged)]
     OneIsBetter(String),
+    /// Every image of the `comparison`, from best to worst.
+    #[serde(untagged)]
+    Ranking(Vec<String>),
+    #[serde(untagged)]
+    BestWorst { best: String, worst: String },
+    /// Point on the rating scale of the `project`, from 1.
+    #[serde(untagged)]
+    Rating(i64),
 }
 
+/// Answers that can be enabled per dirname, besides picking images.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Answer {
+    Equal,
+    Different,
+    CannotTell,
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Answer::Equal => "equal",
+            Answer::Different => "different",
+            Answer::CannotTell => "cannot_tell",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl VoteValue {
+    /// The `Answer` the vote gives, unless it picks images or rates one.
+    pub(crate) fn answer(&self) -> Option<Answer> {
+        match self {
+            VoteValue::Equal => Some(Answer::Equal),
+            VoteValue::Different => Some(Answer::Different),
+            VoteValue::CannotTell => Some(Answer::CannotTell),
+            _ => None,
+        }
+    }
+
+    /// Orders the `images` of a `comparison` as far as the vote does,
+    /// from most to least preferred, in tiers of indexes into `images`
+    /// that the vote leaves unordered among themselves. A `different` or
+    /// `cannot_tell` vote or a rating orders nothing. Returns `None` if the
+    /// vote names an image not in `images`.
+    pub(crate) fn tiers(&self, images: &[&str]) -> Option<Vec<Vec<usize>>> {
+        let position =
+            |image: &str| images.iter().position(|path| *path == image);
+        let others = |excluded: &[usize]| -> Vec<usize> {
+            (0..images.len())
+                .filter(|index| !excluded.contains(index))
+                .collect()
+        };
+
+        let tiers = match self {
+            VoteValue::Equal => vec![others(&[])],
+            VoteValue::Different
+            | VoteValue::CannotTell
+            | VoteValue::Rating(_) => Vec::new(),
+            VoteValue::OneIsBetter(best) => {
+                let best = position(best)?;
+                vec![vec![best], others(&[best])]
+            },
+            VoteValue::Ranking(ranking) => ranking
+                .iter()
+                .map(|image| position(image).map(|index| vec![index]))
+                .collect::<Option<Vec<Vec<usize>>>>()?,
+            VoteValue::BestWorst { best, worst } => {
+                let best = position(best)?;
+                let worst = position(worst)?;
+                vec![vec![best], others(&[best, worst]), vec![worst]]
+            },
+        };
+
+        Some(tiers.into_iter().filter(|tier| !tier.is_empty()).collect())
+    }
+}
+
+/// Most votes accepted by `create_votes` at once.
+pub(crate) const MAX_BATCH_SIZE: usize = 500;
+
+/// Creates the `vote` in the transaction of the request, which marks its
+/// `user` for an `average_lambda` refresh once committed.
 pub(crate) async fn create_vote(
     vote: &Vote,
     connection: &mut SqliteConnection,
 ) -> Result<Vote, QueryError> {
-    let _ = super::user::get_user(*vote.user_id, connection).await?;
+    insert_vote(vote, connection).await
+}
 
-    if let VoteValue::OneIsBetter(image) = &vote.vote_value {
-        get_comparison_images(*vote.comparison_id, connection)
-            .await?
-            .iter()
-            .any(|path| *path.path() == *image)
-            .then_some(())
-            .ok_or(QueryError::RowNotFound(
-                "`image` not found for requested `comparison`".to_string(),
-            ))?;
+/// Creates the queued `votes` in one transaction, validating each like
+/// `create_vote`. A vote whose `idempotency_key` the `user` already used
+/// isn't created again, the stored one is returned instead. Invalid votes
+/// are reported in their result without failing the others. The `user`s
+/// who voted are marked for an `average_lambda` refresh once committed.
+pub(crate) async fn create_votes(
+    votes: Vec<BatchedVote>,
+    ip_addr: Option<String>,
+    stale_lambdas: &StaleLambdas,
+    connection: &mut SqliteConnection,
+) -> Result<Vec<BatchedVoteResult>, QueryError> {
+    if votes.len() > MAX_BATCH_SIZE {
+        return Err(QueryError::InvalidInput(format!(
+            "At most {MAX_BATCH_SIZE} votes can be submitted at once"
+        )));
     }
 
-    let vote_value_as_str = vote.vote_value.to_string();
+    let mut results = Vec::with_capacity(votes.len());
+    let mut user_ids = BTreeSet::new();
+
+    let mut transaction = connection.begin().await?;
+    for batched_vote in votes {
+        let BatchedVote {
+            idempotency_key,
+            client_created_at,
+            mut vote,
+        } = batched_vote;
+        vote.idempotency_key = Some(idempotency_key.clone());
+        vote.client_created_at = Some(client_created_at);
+        vote.ip_addr.clone_from(&ip_addr);
 
+        let stored_vote = get_vote_by_idempotency_key(
+            *vote.user_id,
+            &idempotency_key,
+            &mut transaction,
+        )
+        .await?;
+        let result = match stored_vote {
+            Some(vote) => Ok((Status::Ok, vote)),
+            None => insert_vote(&vote, &mut transaction)
+                .await
+                .map(|vote| (Status::Created, vote)),
+        };
+
+        let result = match result {
+            Ok((status, vote)) => {
+                user_ids.insert(*vote.user_id);
+                BatchedVoteResult {
+                    idempotency_key,
+                    status: status.code,
+                    vote: Some(vote),
+                    error: None,
+                }
+            },
+            Err(QueryError::Sqlx(error)) => return Err(error.into()),
+            Err(error) => {
+                let status = match error {
+                    QueryError::RowNotFound(_) => Status::UnprocessableEntity,
+                    _ => error.default_status(),
+                };
+                BatchedVoteResult {
+                    idempotency_key,
+                    status: status.code,
+                    vote: None,
+                    error: Some(error),
+                }
+            },
+        };
+        results.push(result);
+    }
+    transaction.commit().await?;
+
+    stale_lambdas.mark(user_ids);
+
+    Ok(results)
+}
+
+async fn get_vote_by_idempotency_key(
+    user_id: Uuid,
+    idempotency_key: &str,
+    connection: &mut SqliteConnection,
+) -> Result<Option<Vote>, QueryError> {
     sqlx::query_as!(
         Vote,
-        "INSERT INTO vote (comparison_id, user_id, vote_value, ip_addr) \
-         VALUES (?1, ?2, ?3, ?4) RETURNING id, comparison_id, user_id, \
-         vote_value, created_at as \"created_at: _\", ip_addr",
+        "SELECT id, comparison_id, user_id, vote_value, created_at as \
+         \"created_at: _\", ip_addr, repetition, retracted_at as \
+         \"retracted_at: _\", response_time_ms, viewport_width, \
+         viewport_height, device_pixel_ratio, confidence, idempotency_key, \
+         client_created_at as \"client_created_at: _\" FROM vote WHERE \
+         user_id = ? AND idempotency_key = ?",
+        user_id,
+        idempotency_key,
+    )
+    .fetch_optional(connection)
+    .await
+    .map_err(|error| error.into())
+}
+
+/// Validates and stores a `vote`, without updating the `average_lambda`
+/// of its `user`.
+async fn insert_vote(
+    vote: &Vote,
+    connection: &mut SqliteConnection,
+) -> Result<Vote, QueryError> {
+    let _ = super::user::get_user(*vote.user_id, connection).await?;
+
+    check_vote_value(*vote.comparison_id, &vote.vote_value, connection).await?;
+    check_client_metadata(vote)?;
+
+    let vote_value_as_str = vote.vote_value.to_string();
+
+    // a repeat vote gets the next `repetition` if the `project` of the
+    // dirname allows it, otherwise 0 again, which the unique index rejects;
+    // `gold_passed` stays null unless the `comparison` is a gold one
+    let vote = sqlx::query_as!(
+        Vote,
+        "INSERT INTO vote (comparison_id, user_id, vote_value, ip_addr, \
+         repetition, response_time_ms, viewport_width, viewport_height, \
+         device_pixel_ratio, confidence, idempotency_key, client_created_at, \
+         gold_passed) VALUES (?1, ?2, ?3, ?4, CASE WHEN (SELECT \
+         project.allow_repeat_votes FROM comparison INNER JOIN project ON \
+         project.dirname = comparison.dirname WHERE comparison.id = ?1) THEN \
+         (SELECT COUNT(*) FROM vote WHERE comparison_id = ?1 AND user_id = \
+         ?2) ELSE 0 END, (SELECT CAST(round((julianday('now') - \
+         julianday(issued_at)) * 86400000) AS INTEGER) FROM assignment WHERE \
+         comparison_id = ?1 AND user_id = ?2), ?5, ?6, ?7, ?8, ?9, ?10, \
+         (SELECT expected_vote_value = ?3 FROM comparison WHERE id = ?1)) \
+         RETURNING id, comparison_id, user_id, vote_value, created_at as \
+         \"created_at: _\", ip_addr, repetition, retracted_at as \
+         \"retracted_at: _\", response_time_ms, viewport_width, \
+         viewport_height, device_pixel_ratio, confidence, idempotency_key, \
+         client_created_at as \"client_created_at: _\"",
         *vote.comparison_id,
         *vote.user_id,
         vote_value_as_str,
         vote.ip_addr,
+        vote.viewport_width,
+        vote.viewport_height,
+        vote.device_pixel_ratio,
+        vote.confidence,
+        vote.idempotency_key,
+        vote.client_created_at,
     )
-    .fetch_one(connection)
+    .fetch_one(&mut *connection)
     .await
-    .map_err(|error| error.into())
+    .map_err(|error| match error {
+        sqlx::Error::Database(error) if error.is_unique_violation() => {
+            QueryError::Conflict(
+                "`user` already voted on requested `comparison`".to_string(),
+            )
+        },
+        error => error.into(),
+    })?;
+
+    // the assignment only times the first vote after handing out the
+    // `comparison`, a repeat vote needs it handed out again
+    sqlx::query!(
+        "DELETE FROM assignment WHERE comparison_id = ? AND user_id = ?",
+        *vote.comparison_id,
+        *vote.user_id,
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(vote)
+}
+
+/// Changes the `vote_value` of a `vote`, keeping the previous one in its
+/// edit history. The `vote` is checked in the same transaction, so
+/// concurrent edits can't both pass the checks. Its `user` is marked for an
+/// `average_lambda` refresh once committed.
+pub(crate) async fn change_vote(
+    id: i64,
+    change: &VoteChange,
+    stale_lambdas: &StaleLambdas,
+    connection: &mut SqliteConnection,
+) -> Result<Vote, QueryError> {
+    let mut transaction = connection.begin().await?;
+    let vote = get_editable_vote(id, *change.user_id, &mut transaction).await?;
+    check_vote_value(*vote.comparison_id, &change.vote_value, &mut transaction)
+        .await
+        .map_err(|error| match error {
+            QueryError::RowNotFound(message) => {
+                QueryError::InvalidInput(message)
+            },
+            error => error,
+        })?;
+
+    let vote_value_as_str = change.vote_value.to_string();
+
+    sqlx::query!(
+        "INSERT INTO vote_edit (vote_id, action, vote_value) SELECT id, \
+         'change', vote_value FROM vote WHERE id = ?",
+        id,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    let vote = sqlx::query_as!(
+        Vote,
+        "UPDATE vote SET vote_value = ?1, gold_passed = (SELECT \
+         expected_vote_value = ?1 FROM comparison WHERE comparison.id = \
+         vote.comparison_id) WHERE id = ?2 RETURNING id, comparison_id, \
+         user_id, vote_value, created_at as \"created_at: _\", ip_addr, \
+         repetition, retracted_at as \"retracted_at: _\", response_time_ms, \
+         viewport_width, viewport_height, device_pixel_ratio, confidence, \
+         idempotency_key, client_created_at as \"client_created_at: _\"",
+        vote_value_as_str,
+        id,
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+
+    stale_lambdas.mark([*vote.user_id]);
+
+    Ok(vote)
+}
+
+/// Retracts a `vote`, keeping it along with its edit history. The `vote`
+/// is checked in the same transaction and its `user` marked for an
+/// `average_lambda` refresh, like in `change_vote`.
+pub(crate) async fn retract_vote(
+    id: i64,
+    user_id: Uuid,
+    stale_lambdas: &StaleLambdas,
+    connection: &mut SqliteConnection,
+) -> Result<Vote, QueryError> {
+    let mut transaction = connection.begin().await?;
+    let _ = get_editable_vote(id, user_id, &mut transaction).await?;
+
+    sqlx::query!(
+        "INSERT INTO vote_edit (vote_id, action, vote_value) SELECT id, \
+         'retract', vote_value FROM vote WHERE id = ?",
+        id,
+    )
+    .execute(&mut *transaction)
+    .await?;
+    let vote = sqlx::query_as!(
+        Vote,
+        "UPDATE vote SET retracted_at = datetime('now') WHERE id = ? \
+         RETURNING id, comparison_id, user_id, vote_value, created_at as \
+         \"created_at: _\", ip_addr, repetition, retracted_at as \
+         \"retracted_at: _\", response_time_ms, viewport_width, \
+         viewport_height, device_pixel_ratio, confidence, idempotency_key, \
+         client_created_at as \"client_created_at: _\"",
+        id,
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+
+    stale_lambdas.mark([*vote.user_id]);
+
+    Ok(vote)
+}
+
+/// Returns the `vote` if `user_id` cast it, it is the last vote of theirs
+/// still standing and the edit window of its dirname hasn't passed yet.
+async fn get_editable_vote(
+    id: i64,
+    user_id: Uuid,
+    connection: &mut SqliteConnection,
+) -> Result<Vote, QueryError> {
+    let vote = sqlx::query_as!(
+        Vote,
+        "SELECT id, comparison_id, user_id, vote_value, created_at as \
+         \"created_at: _\", ip_addr, repetition, retracted_at as \
+         \"retracted_at: _\", response_time_ms, viewport_width, \
+         viewport_height, device_pixel_ratio, confidence, idempotency_key, \
+         client_created_at as \"client_created_at: _\" FROM vote WHERE id = ?",
+        id,
+    )
+    .fetch_one(&mut *connection)
+    .await
+    .map_err(|error| match error {
+        sqlx::Error::RowNotFound => QueryError::RowNotFound(
+            "`vote` with requested id not found".to_string(),
+        ),
+        error => error.into(),
+    })?;
+
+    if *vote.user_id != user_id {
+        return Err(QueryError::Forbidden(
+            "`vote` was cast by another `user`".to_string(),
+        ));
+    }
+    if vote.retracted_at.is_some() {
+        return Err(QueryError::Conflict(
+            "`vote` was already retracted".to_string(),
+        ));
+    }
+
+    let dirname = sqlx::query_scalar!(
+        "SELECT dirname FROM comparison WHERE id = ?",
+        *vote.comparison_id,
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+    let project =
+        super::project::get_project(&dirname, &mut *connection).await?;
+    if Utc::now() - vote.created_at
+        >= Duration::seconds(project.vote_edit_window)
+    {
+        return Err(QueryError::Conflict(
+            "`vote` can't be changed anymore".to_string(),
+        ));
+    }
+
+    let later_votes = sqlx::query_scalar!(
+        "SELECT COUNT(*) as \"count: i64\" FROM vote WHERE user_id = ? AND id \
+         > ? AND retracted_at IS NULL",
+        user_id,
+        id,
+    )
+    .fetch_one(connection)
+    .await?;
+    if later_votes > 0 {
+        return Err(QueryError::Conflict(
+            "Only the last `vote` of a `user` can be changed".to_string(),
+        ));
+    }
+
+    Ok(vote)
+}
+
+/// Checks that the viewport size and device pixel ratio reported by the
+/// client, if any, are positive, and the confidence is on its scale.
+fn check_client_metadata(vote: &Vote) -> Result<(), QueryError> {
+    if vote
+        .confidence
+        .is_some_and(|confidence| !(1..=5).contains(&confidence))
+    {
+        return Err(QueryError::InvalidInput(
+            "`confidence` must be between 1 and 5".to_string(),
+        ));
+    }
+
+    let viewport = [
+        ("viewport_width", vote.viewport_width),
+        ("viewport_height", vote.viewport_height),
+    ];
+    for (name, value) in viewport {
+        if value.is_some_and(|value| value <= 0) {
+            return Err(QueryError::InvalidInput(format!(
+                "`{name}` must be positive"
+            )));
+        }
+    }
+
+    if vote
+        .device_pixel_ratio
+        .is_some_and(|ratio| !ratio.is_finite() || ratio <= 0.0)
+    {
+        return Err(QueryError::InvalidInput(
+            "`device_pixel_ratio` must be positive".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that the `comparison` exists, that the answer given is enabled
+/// by its `project`, that every image the `vote_value` names is one of its
+/// images, and that a ranking lists each of them once. A `comparison` of
+/// a single image must be rated within the scale of its `project`, unless
+/// the `user` can't tell, and only those can be rated.
+pub(crate) async fn check_vote_value(
+    comparison_id: Uuid,
+    vote_value: &VoteValue,
+    connection: &mut SqliteConnection,
+) -> Result<(), QueryError> {
+    let comparison =
+        get_comparison_images(comparison_id, &mut *connection).await?;
+    let paths: Vec<&str> = comparison
+        .images
+        .iter()
+        .map(|image| image.path().as_str())
+        .collect();
+    let project =
+        super::project::get_project(&comparison.dirname, connection).await?;
+
+    if let Some(answer) = vote_value.answer() {
+        if !project.answers.0.contains(&answer) {
+            return Err(QueryError::InvalidInput(format!(
+                "`{answer}` is not an answer enabled for the dirname"
+            )));
+        }
+    }
+
+    match (vote_value, paths.len()) {
+        (VoteValue::Rating(rating), 1) => {
+            if !(1..=project.rating_scale).contains(rating) {
+                return Err(QueryError::InvalidInput(format!(
+                    "`vote_value` must be between 1 and {}",
+                    project.rating_scale
+                )));
+            }
+
+            return Ok(());
+        },
+        (VoteValue::Rating(_), _) => {
+            return Err(QueryError::InvalidInput(
+                "Only `comparison`s of a single image can be rated".to_string(),
+            ));
+        },
+        (VoteValue::CannotTell, _) => return Ok(()),
+        (_, 1) => {
+            return Err(QueryError::InvalidInput(
+                "`comparison`s of a single image can only be rated".to_string(),
+            ));
+        },
+        _ => {},
+    }
+
+    let tiers = vote_value.tiers(&paths).ok_or(QueryError::RowNotFound(
+        "`image` not found for requested `comparison`".to_string(),
+    ))?;
+
+    match vote_value {
+        VoteValue::Ranking(ranking) => {
+            let ranked: BTreeSet<usize> = tiers.into_iter().flatten().collect();
+            if ranking.len() != paths.len() || ranked.len() != paths.len() {
+                return Err(QueryError::InvalidInput(
+                    "`vote_value` must rank every image of the `comparison` \
+                     once"
+                        .to_string(),
+                ));
+            }
+        },
+        VoteValue::BestWorst { best, worst } if best == worst => {
+            return Err(QueryError::InvalidInput(
+                "`best` and `worst` must be different images".to_string(),
+            ));
+        },
+        _ => {},
+    }
+
+    Ok(())
 }
 
 impl From<String> for VoteValue {
     fn from(value: String) -> Self {
+        // image paths start with `/`, so they can't be mistaken for the
+        // JSON of rankings and best-worst selections, or for ratings
+        if value.starts_with(['[', '{']) {
+            if let Ok(vote_value) = json::from_str(&value) {
+                return vote_value;
+            }
+        }
+        if let Ok(rating) = value.parse() {
+            return VoteValue::Rating(rating);
+        }
+
         match value.as_str() {
             "equal" => VoteValue::Equal,
             "different" => VoteValue::Different,
+            "cannot_tell" => VoteValue::CannotTell,
             _ => VoteValue::OneIsBetter(value),
         }
     }
@@ -89,21 +669,26 @@ impl From<String> for VoteValue {
 impl Display for VoteValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
-            VoteValue::Equal => &"equal".to_string(),
-            VoteValue::Different => &"different".to_string(),
-            VoteValue::OneIsBetter(s) => s,
+            VoteValue::Equal => "equal".to_string(),
+            VoteValue::Different => "different".to_string(),
+            VoteValue::CannotTell => "cannot_tell".to_string(),
+            VoteValue::OneIsBetter(s) => s.clone(),
+            VoteValue::Rating(rating) => rating.to_string(),
+            VoteValue::Ranking(_) | VoteValue::BestWorst { .. } => {
+                json::to_string(self).map_err(|_| std::fmt::Error)?
+            },
         };
         write!(f, "{}", s)
     }
 }
 
-async fn get_comparison_images(
+async fn get_comparison_images<'a>(
     id: Uuid,
     connection: &mut SqliteConnection,
-) -> Result<SqliteArray, QueryError> {
+) -> Result<ComparisonImages<'a>, QueryError> {
     sqlx::query_as!(
         ComparisonImages,
-        "SELECT images FROM comparison WHERE id = ?",
+        "SELECT dirname, images FROM comparison WHERE id = ?",
         id,
     )
     .fetch_one(connection)
@@ -114,9 +699,9 @@ async fn get_comparison_images(
         ),
         error => error.into(),
     })
-    .map(|comparison| comparison.images)
 }
 
 struct ComparisonImages<'a> {
+    dirname: String,
     images: SqliteArray<'a>,
 }
@@ -66,6 +66,18 @@ pub(crate) async fn unauthorized(request: &Request<'_>) -> Unauthorized {
     }
 }
 
+#[catch(403)]
+pub(crate) async fn forbidden(
+    request: &Request<'_>,
+) -> Json<ResponseBody<(), String>> {
+    let request_id = request
+        .guard::<&RequestId>()
+        .await
+        .expect("BUG: RequestId should return Outcome::Success");
+
+    Json((request_id, Err("Forbidden".to_string())).into())
+}
+
 #[derive(rocket::response::Responder)]
 pub(crate) struct Unauthorized {
     body: Json<ResponseBody<(), String>>,
@@ -5,9 +5,12 @@ mod response;
 #[macro_use]
 extern crate rocket;
 
-use std::path::{
-    Path,
-    PathBuf,
+use std::{
+    error::Error,
+    path::{
+        Path,
+        PathBuf,
+    },
 };
 
 use rocket::{
@@ -18,6 +21,7 @@ use rocket::{
         Method,
     },
     Build,
+    Orbit,
     Rocket,
 };
 use rocket_db_pools::Database;
@@ -52,7 +56,10 @@ pub fn rocket<S: Into<String>, P: AsRef<Path>>(
         .attach(CORS { allowed_origin })
         .attach(CacheControl)
         .attach(DbPool::init())
+        .attach(DbForeignKeys)
         .attach(DbMigrations)
+        .attach(AdminAudit)
+        .attach(AverageLambdaRefresh)
         .register(
             "/",
             catchers![
@@ -60,6 +67,7 @@ pub fn rocket<S: Into<String>, P: AsRef<Path>>(
                 crate::catchers::unprocessable_entity,
                 crate::catchers::not_found,
                 crate::catchers::unauthorized,
+                crate::catchers::forbidden,
             ],
         )
         .mount(
@@ -69,14 +77,36 @@ pub fn rocket<S: Into<String>, P: AsRef<Path>>(
                 crate::api::healthcheck::handler::healthcheck,
                 crate::api::comparison::handler::get_comparison_dirnames,
                 crate::api::comparison::handler::get_comparison_for_user,
+                crate::api::comparison::handler::list_comparisons,
+                crate::api::comparison::handler::get_comparison,
+                crate::api::comparison::handler::set_gold_standard,
+                crate::api::comparison::handler::delete_comparison,
+                crate::api::comparison::handler::delete_dirname,
+                crate::api::ranking::handler::get_ranking,
+                crate::api::ranking::handler::get_ranking_of_dirname,
                 crate::api::user::handler::get_user,
                 crate::api::user::handler::generate_user,
                 crate::api::vote::handler::vote,
+                crate::api::vote::handler::votes,
+                crate::api::vote::handler::change_vote,
+                crate::api::vote::handler::retract_vote,
                 crate::api::admin::handler::generate_comparisons,
+                crate::api::admin::handler::list_admins,
+                crate::api::admin::handler::create_admin,
+                crate::api::admin::handler::update_admin_access,
+                crate::api::admin::handler::rotate_admin_key,
+                crate::api::admin::handler::revoke_admin,
+                crate::api::export::handler::export_votes,
+                crate::api::audit::handler::list_audit_entries,
+                crate::api::project::handler::get_project,
+                crate::api::project::handler::set_project,
             ],
         )
         .mount(STATIC_ROUTE, FileServer::from(&static_dir.path))
         .manage(static_dir)
+        .manage(crate::api::comparison::DeletionTokens::default())
+        .manage(crate::api::comparison::ScoreCache::default())
+        .manage(crate::api::user::StaleLambdas::default())
 }
 
 static STATIC_ROUTE: &'static str = "/static/images";
@@ -89,6 +119,55 @@ pub(crate) struct StaticDir {
 #[database("main")]
 pub(crate) struct DbPool(sqlx::SqlitePool);
 
+/// Makes every connection of the pool enforce foreign keys. The pool is
+/// configured from a database url, which can't carry pragmas, so the
+/// connections it opened before this fairing are switched over as well.
+struct DbForeignKeys;
+
+#[rocket::async_trait]
+impl fairing::Fairing for DbForeignKeys {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "SQLite Foreign Keys",
+            kind: fairing::Kind::Ignite,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        if let Some(pool) = DbPool::fetch(&rocket) {
+            match enforce_foreign_keys(pool).await {
+                Ok(_) => Ok(rocket),
+                Err(error) => {
+                    error!("Enabling foreign keys failed: {error}");
+                    Err(rocket)
+                },
+            }
+        } else {
+            Err(rocket)
+        }
+    }
+}
+
+async fn enforce_foreign_keys(
+    pool: &sqlx::SqlitePool,
+) -> Result<(), sqlx::Error> {
+    pool.set_connect_options(
+        (*pool.connect_options()).clone().foreign_keys(true),
+    );
+
+    // hold the idle connections at once, so each of them gets the pragma
+    let mut connections = Vec::with_capacity(pool.num_idle());
+    for _ in 0..pool.num_idle() {
+        let mut connection = pool.acquire().await?;
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *connection)
+            .await?;
+        connections.push(connection);
+    }
+
+    Ok(())
+}
+
 struct DbMigrations;
 
 #[rocket::async_trait]
@@ -102,7 +181,7 @@ impl fairing::Fairing for DbMigrations {
 
     async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
         if let Some(connection) = DbPool::fetch(&rocket) {
-            match sqlx::migrate!().run(&**connection).await {
+            match migrate(&**connection).await {
                 Ok(_) => Ok(rocket),
                 Err(error) => {
                     error!("Migrations failed: {error}");
@@ -115,6 +194,75 @@ impl fairing::Fairing for DbMigrations {
     }
 }
 
+/// Records every request made by an `admin` in the audit log, once it has
+/// been handled.
+struct AdminAudit;
+
+#[rocket::async_trait]
+impl fairing::Fairing for AdminAudit {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Admin Audit Log",
+            kind: fairing::Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(
+        &self,
+        request: &'r rocket::Request<'_>,
+        response: &mut rocket::Response<'r>,
+    ) {
+        let trail = crate::api::audit::AuditTrail::of(request);
+        let Some(pool) = DbPool::fetch(request.rocket()) else {
+            return;
+        };
+        if trail.admin_id().is_none() {
+            return;
+        }
+
+        let result = match pool.acquire().await {
+            Ok(mut connection) => {
+                crate::api::audit::record_request(
+                    request,
+                    response.status(),
+                    &mut *connection,
+                )
+                .await
+            },
+            Err(error) => Err(error.into()),
+        };
+
+        if let Err(error) = result {
+            error!("Recording admin request failed: {error}");
+        }
+    }
+}
+
+/// Recomputes the `average_lambda` of the `user`s whose votes changed in
+/// the background, on a connection of its own, once the server is up.
+struct AverageLambdaRefresh;
+
+#[rocket::async_trait]
+impl fairing::Fairing for AverageLambdaRefresh {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Average Lambda Refresh",
+            kind: fairing::Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let stale_lambdas =
+            rocket.state::<crate::api::user::StaleLambdas>().cloned();
+        match (DbPool::fetch(rocket), stale_lambdas) {
+            (Some(pool), Some(stale_lambdas)) => {
+                rocket::tokio::spawn(stale_lambdas.refresh(pool.0.clone()));
+            },
+            _ => error!("Starting the `average_lambda` refresh failed"),
+        }
+    }
+}
+
 struct CORS {
     allowed_origin: String,
 }
@@ -136,11 +284,11 @@ impl fairing::Fairing for CORS {
         if request.method() == Method::Options {
             response.set_header(Header::new(
                 "Access-Control-Allow-Methods",
-                "OPTIONS, POST, DELETE, GET",
+                "OPTIONS, POST, PUT, DELETE, GET",
             ));
             response.set_header(Header::new(
                 "Access-Control-Allow-Headers",
-                "Content-Type, Authorization",
+                "Content-Type, Authorization, Idempotency-Key",
             ));
         }
 
@@ -185,3 +333,77 @@ impl fairing::Fairing for CacheControl {
         }
     }
 }
+
+/// Runs the migrations, then hashes any admin key still stored in
+/// plaintext. Both run on one connection, as an in-memory database only
+/// exists for the connection that opened it.
+async fn migrate(pool: &sqlx::SqlitePool) -> Result<(), Box<dyn Error>> {
+    let mut connection = pool.acquire().await?;
+    sqlx::migrate!().run_direct(&mut *connection).await?;
+
+    let hashed =
+        crate::api::admin::key::hash_legacy_keys(&mut connection).await?;
+    if hashed > 0 {
+        info!("Hashed {hashed} plaintext admin key(s)");
+    }
+
+    Ok(())
+}
+
+/// Creates a new `owner` admin with access to every dirname, returning its
+/// id and key. The key can't be retrieved later.
+pub async fn create_admin(
+    connection_options: SqliteConnectOptions,
+) -> Result<(i64, String), Box<dyn Error>> {
+    let pool = sqlx::SqlitePool::connect_with(connection_options).await?;
+    migrate(&pool).await?;
+
+    let mut connection = pool.acquire().await?;
+    let access = crate::api::admin::key::AdminAccess {
+        role: crate::api::admin::AdminRole::Owner,
+        dirnames: Vec::new(),
+    };
+    let admin =
+        crate::api::admin::key::create_admin(&access, &mut connection).await?;
+
+    Ok((admin.id, admin.key))
+}
+
+#[cfg(test)]
+mod test {
+    use rocket_db_pools::Database;
+    use sqlx::sqlite::{
+        SqliteConnectOptions,
+        SqlitePoolOptions,
+    };
+
+    #[sqlx::test(fixtures(path = "../fixtures", scripts("users")))]
+    async fn pool_rejects_orphan_votes(
+        _: SqlitePoolOptions,
+        db_options: SqliteConnectOptions,
+    ) {
+        let rocket = super::rocket(
+            "*",
+            "tests/static_dir/ok",
+            db_options.foreign_keys(false),
+        )
+        .ignite()
+        .await
+        .expect("rocket to ignite");
+        let pool = super::DbPool::fetch(&rocket).expect("pool to be present");
+
+        let result = sqlx::query(
+            "INSERT INTO vote (comparison_id, user_id, vote_value) VALUES (?, \
+             ?, 'equal')",
+        )
+        .bind(uuid::uuid!("44444444-4444-4444-4444-444444444444"))
+        .bind(uuid::uuid!("3fa85f64-5717-4562-b3fc-2c963f66afa6"))
+        .execute(&**pool)
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(sqlx::Error::Database(error)) if error.is_foreign_key_violation()
+        ));
+    }
+}
@@ -18,6 +18,25 @@ async fn main() -> Result<(), rocket::Error> {
 
     dotenvy::dotenv().ok();
 
+    let connection_options = std::env::var("DATABASE_URL")
+        .expect("`DATABASE_URL` to be set in .env");
+    let connection_options =
+        SqliteConnectOptions::from_str(&connection_options)
+            .expect("Url to be valid");
+
+    // `create-admin` prints the key of a new admin instead of serving
+    if std::env::args().nth(1).as_deref() == Some("create-admin") {
+        match image_compare_api::create_admin(connection_options).await {
+            Ok((id, key)) => println!("Created admin {id} with key: {key}"),
+            Err(error) => {
+                error!("{}", error);
+                panic!("failed to create admin");
+            },
+        }
+
+        return Ok(());
+    }
+
     let allowed_origin = std::env::var("ALLOWED_ORIGIN")
         .expect("`ALLOWED_ORIGIN` to be set in .env");
 
@@ -25,12 +44,6 @@ async fn main() -> Result<(), rocket::Error> {
         std::env::var("STATIC_DIR").expect("`STATIC_DIR` to be set in .env");
     let static_dir = PathBuf::from(static_dir);
 
-    let connection_options = std::env::var("DATABASE_URL")
-        .expect("`DATABASE_URL` to be set in .env");
-    let connection_options =
-        SqliteConnectOptions::from_str(&connection_options)
-            .expect("Url to be valid");
-
     let _rocket = image_compare_api::rocket(
         allowed_origin,
         static_dir,
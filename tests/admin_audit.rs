@@ -0,0 +1,229 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use rocket::{
+    fs::relative,
+    http::{
+        Header,
+        Status,
+    },
+    local::asynchronous::Client,
+    uri,
+};
+use serde::Deserialize;
+
+use crate::common::{
+    make_api_test,
+    ApiResponse,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct AuditEntry {
+    admin_id: i64,
+    method: String,
+    route: String,
+    path: String,
+    parameters: BTreeMap<String, String>,
+    status: u16,
+    affected_rows: Option<i64>,
+    ip_addr: Option<String>,
+    request_id: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditPage {
+    entries: Vec<AuditEntry>,
+    total: i64,
+}
+
+async fn get_audit_page(client: &Client, query: &str) -> AuditPage {
+    client
+        .get(format!("/api/admin/audit{query}"))
+        .header(Header::new(
+            "Authorization",
+            "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+        ))
+        .dispatch()
+        .await
+        .into_json::<ApiResponse<AuditPage, ()>>()
+        .await
+        .and_then(|json| json.data)
+        .expect("audit page to be present")
+}
+
+mod generate_comparisons_is_recorded {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison?incremental=true"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let with_parameters_and_created_rows = |response| {
+            assert_eq!(response.status(), Status::Created);
+
+            let page = get_audit_page(&client, "").await;
+
+            assert_eq!(page.total, 1);
+            let entry = &page.entries[0];
+            assert_eq!(entry.admin_id, 1);
+            assert_eq!(entry.method, "POST");
+            assert_eq!(entry.route, "/api/admin/comparison");
+            assert_eq!(entry.status, 201);
+            assert_eq!(
+                entry.parameters.get("incremental").map(String::as_str),
+                Some("true")
+            );
+            assert!(entry.affected_rows.is_some_and(|rows| rows > 0));
+        };
+    }
+}
+
+mod delete_comparison_is_recorded {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons")]
+        let request = |client| {
+            client
+                .delete(uri!("/api/admin/comparison/33993492-d8ce-4248-a93d-caf88baed82e"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let with_path_parameter = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            let page = get_audit_page(&client, "?method=delete").await;
+
+            assert_eq!(page.total, 1);
+            let entry = &page.entries[0];
+            assert_eq!(entry.route, "/api/admin/comparison/<id>");
+            assert_eq!(entry.status, 200);
+            assert_eq!(entry.affected_rows, Some(1));
+            assert_eq!(
+                entry.parameters.get("id").map(String::as_str),
+                Some("33993492-d8ce-4248-a93d-caf88baed82e")
+            );
+        };
+    }
+}
+
+mod forbidden_request_is_recorded {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer a1b2c3d4e5f60718293a4b5c6d7e8f90",
+                ))
+        };
+
+        #[test_request]
+        let with_403_status = |response| {
+            assert_eq!(response.status(), Status::Forbidden);
+
+            let page = get_audit_page(&client, "?admin_id=3").await;
+
+            assert_eq!(page.total, 1);
+            assert_eq!(page.entries[0].status, 403);
+            assert_eq!(page.entries[0].affected_rows, None);
+        };
+    }
+}
+
+mod unauthenticated_request_is_not_recorded {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client.post(uri!("/api/admin/comparison"))
+        };
+
+        #[test_request]
+        let leaves_log_empty = |response| {
+            assert_eq!(response.status(), Status::Unauthorized);
+
+            let page = get_audit_page(&client, "").await;
+
+            assert_eq!(page.total, 0);
+        };
+    }
+}
+
+mod list_audit_entries_as_scoped_owner {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/audit"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer c3d4e5f60718293a4b5c6d7e8f90a1b2",
+                ))
+        };
+
+        #[test_request]
+        let returns_403_forbidden = |response| {
+            assert_eq!(response.status(), Status::Forbidden);
+        };
+    }
+}
+
+mod list_audit_entries_with_invalid_date {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/audit?created_after=yesterday"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
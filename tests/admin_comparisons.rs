@@ -0,0 +1,569 @@
+mod common;
+
+use std::{
+    collections::BTreeMap,
+    time::Duration,
+};
+
+use rocket::{
+    fs::relative,
+    http::{
+        uri::Origin,
+        Header,
+        Status,
+    },
+    uri,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::common::{
+    make_api_test,
+    ApiResponse,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct Comparison {
+    id: Uuid,
+    dirname: String,
+    images: Vec<Origin<'static>>,
+    created_by: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ComparisonPage {
+    comparisons: Vec<Comparison>,
+    page: i64,
+    total: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ComparisonDetails {
+    id: Uuid,
+    retired_at: Option<String>,
+    expected_vote_value: Option<String>,
+    vote_count: i64,
+    votes: BTreeMap<String, i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DirnameDeletion {
+    dirname: String,
+    comparisons: i64,
+    votes: i64,
+    confirm: Option<String>,
+}
+
+mod list_comparisons_of_dirname {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/comparison?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_only_comparisons_of_dirname = |response| {
+            let json = response.into_json::<ApiResponse<ComparisonPage, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.total, 2);
+            assert_eq!(data.comparisons.len(), 2);
+            for comparison in data.comparisons {
+                assert_eq!(comparison.dirname, "folder_b/folder_c");
+            }
+        };
+    }
+}
+
+mod list_comparisons_paged {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/comparison?created_by=1&page=2&per_page=3"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_requested_page = |response| {
+            let json = response.into_json::<ApiResponse<ComparisonPage, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.page, 2);
+            assert_eq!(data.total, 4);
+            assert_eq!(data.comparisons.len(), 1);
+        };
+    }
+}
+
+mod list_comparisons_created_after {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/comparison?created_after=2100-01-01"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_no_comparisons = |response| {
+            let json = response.into_json::<ApiResponse<ComparisonPage, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.total, 0);
+            assert!(data.comparisons.is_empty());
+        };
+    }
+}
+
+mod list_comparisons_with_invalid_date {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/comparison?created_before=yesterday"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+
+        #[test_request]
+        let returns_expected_error = |response| {
+            let json = response.into_json::<ApiResponse<(), String>>()
+                .await;
+            let error = json
+                .expect("json to be preset")
+                .error
+                .expect("error to be present");
+
+            assert_eq!(
+                error,
+                "`created_before` must be an RFC 3339 timestamp or a YYYY-MM-DD day"
+            );
+        };
+    }
+}
+
+mod list_comparisons_unauthorized {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/comparison"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer c3e3a2f7a4bb2f9d1a470660c6d68b09",
+                ))
+        };
+
+        #[test_request]
+        let returns_401_unauthorized = |response| {
+            assert_eq!(response.status(), Status::Unauthorized);
+        };
+    }
+}
+
+mod get_comparison_with_votes {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/comparison/33993492-d8ce-4248-a93d-caf88baed82e"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_vote_tally = |response| {
+            let json = response.into_json::<ApiResponse<ComparisonDetails, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let expected_votes = BTreeMap::from([(
+                "/static/images/image%20A.png".to_string(),
+                2,
+            )]);
+
+            assert_eq!(
+                data.id,
+                Uuid::parse_str("33993492-d8ce-4248-a93d-caf88baed82e").unwrap()
+            );
+            assert_eq!(data.retired_at, None);
+            assert_eq!(data.vote_count, 2);
+            assert_eq!(data.votes, expected_votes);
+        };
+    }
+}
+
+mod get_nonexistent_comparison {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/comparison/00000000-0000-4000-8000-000000000000"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_404_not_found = |response| {
+            assert_eq!(response.status(), Status::NotFound);
+        };
+    }
+}
+
+mod delete_comparison {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .delete(uri!("/api/admin/comparison/33993492-d8ce-4248-a93d-caf88baed82e"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let deletes_comparison_and_its_votes = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            let response = client
+                .get(uri!("/api/admin/comparison/33993492-d8ce-4248-a93d-caf88baed82e"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::NotFound);
+
+            let response = client
+                .get(uri!("/api/admin/votes/export?format=jsonl"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .dispatch()
+                .await;
+            let body = response.into_string().await.expect("body to be present");
+
+            assert_eq!(body.lines().count(), 2);
+        };
+    }
+}
+
+mod delete_dirname_with_confirmation {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .delete(uri!("/api/admin/comparison?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_202_accepted = |response| {
+            assert_eq!(response.status(), Status::Accepted);
+        };
+
+        #[test_request]
+        let deletes_only_once_confirmed = |response| {
+            let json = response.into_json::<ApiResponse<DirnameDeletion, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.dirname, "folder_b/folder_c");
+            assert_eq!(data.comparisons, 2);
+            assert_eq!(data.votes, 1);
+            let token = data.confirm.expect("confirm token to be present");
+
+            let response = client
+                .get(uri!("/api/admin/comparison?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .dispatch()
+                .await;
+            let json = response.into_json::<ApiResponse<ComparisonPage, ()>>()
+                .await;
+            assert_eq!(json.and_then(|json| json.data).map(|data| data.total), Some(2));
+
+            let response = client
+                .delete(format!(
+                    "/api/admin/comparison?dirname=folder_b/folder_c&confirm={token}"
+                ))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Ok);
+
+            let response = client
+                .get(uri!("/api/admin/comparison?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .dispatch()
+                .await;
+            let json = response.into_json::<ApiResponse<ComparisonPage, ()>>()
+                .await;
+            assert_eq!(json.and_then(|json| json.data).map(|data| data.total), Some(0));
+        };
+    }
+}
+
+mod delete_dirname_with_invalid_confirmation {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .delete(uri!(
+                    "/api/admin/comparison?dirname=folder_b/folder_c&confirm=0123456789abcdef"
+                ))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod set_gold_standard {
+    use pretty_assertions::assert_eq;
+    use rocket::serde::json::json;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct User {
+        reliability: Option<f64>,
+        average_lambda: f64,
+    }
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/comparison/33993492-d8ce-4248-a93d-caf88baed82e/gold"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "expected_vote_value": "/static/images/image%20A.png",
+                }))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_expected_vote_value = |response| {
+            let json = response.into_json::<ApiResponse<ComparisonDetails, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(
+                data.expected_vote_value.as_deref(),
+                Some("/static/images/image%20A.png")
+            );
+        };
+
+        #[test_request]
+        let scores_votes_already_cast = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            let json = client
+                .get(uri!("/api/user/3fa85f64-5717-4562-b3fc-2c963f66afa6"))
+                .dispatch()
+                .await
+                .into_json::<ApiResponse<User, ()>>()
+                .await;
+            let reliability = json
+                .and_then(|json| json.data)
+                .and_then(|user| user.reliability);
+
+            assert_eq!(reliability, Some(1.0));
+        };
+
+        #[test_request]
+        let updates_average_lambda_of_voters_in_background = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            // the refresh runs after the response, so wait for it
+            let mut average_lambda = 0.1234;
+            for _ in 0..50 {
+                rocket::tokio::time::sleep(Duration::from_millis(100)).await;
+
+                let json = client
+                    .get(uri!("/api/user/3fa85f64-5717-4562-b3fc-2c963f66afa6"))
+                    .dispatch()
+                    .await
+                    .into_json::<ApiResponse<User, ()>>()
+                    .await;
+                average_lambda = json
+                    .expect("json to be preset")
+                    .data
+                    .expect("data to be present")
+                    .average_lambda;
+                if average_lambda != 0.1234 {
+                    break;
+                }
+            }
+
+            assert_ne!(average_lambda, 0.1234);
+        };
+    }
+}
+
+mod set_gold_standard_with_incorrect_image {
+    use pretty_assertions::assert_eq;
+    use rocket::serde::json::json;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/comparison/33993492-d8ce-4248-a93d-caf88baed82e/gold"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "expected_vote_value": "/static/images/image%20C.png",
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
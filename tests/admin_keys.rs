@@ -0,0 +1,444 @@
+mod common;
+
+use rocket::{
+    fs::relative,
+    http::{
+        Header,
+        Status,
+    },
+    serde::json::json,
+    uri,
+};
+use serde::Deserialize;
+
+use crate::common::{
+    make_api_test,
+    ApiResponse,
+};
+
+#[derive(Debug, Deserialize)]
+struct AdminKey {
+    id: i64,
+    key: String,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct AdminInfo {
+    id: i64,
+    role: String,
+    dirnames: Vec<String>,
+    created_at: String,
+    last_used_at: Option<String>,
+    revoked_at: Option<String>,
+}
+
+mod list_admins {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/admins"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let records_last_use_without_exposing_key = |response| {
+            let body = response.into_string().await.expect("body to be present");
+            assert!(!body.contains("key"));
+
+            let json = rocket::serde::json::from_str::<ApiResponse<Vec<AdminInfo>, ()>>(&body)
+                .expect("json to be valid");
+            let data = json.data.expect("data to be present");
+
+            assert_eq!(data.len(), 1);
+            assert_eq!(data[0].id, 1);
+            assert!(data[0].last_used_at.is_some());
+            assert!(data[0].revoked_at.is_none());
+        };
+    }
+}
+
+mod list_admins_without_key {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client.get(uri!("/api/admin/admins"))
+        };
+
+        #[test_request]
+        let returns_401_unauthorized = |response| {
+            assert_eq!(response.status(), Status::Unauthorized);
+        };
+    }
+}
+
+mod legacy_plaintext_key {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "legacy_admins")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/admins"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer 0f1e2d3c4b5a69788796a5b4c3d2e1f0",
+                ))
+        };
+
+        #[test_request]
+        let is_hashed_and_still_accepted = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+    }
+}
+
+mod create_admin {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/admins"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "role": "owner",
+                }))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let returns_working_key = |response| {
+            let json = response.into_json::<ApiResponse<AdminKey, ()>>().await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.id, 2);
+            assert_eq!(data.key.len(), 32);
+
+            let response = client
+                .get(uri!("/api/admin/admins"))
+                .header(Header::new("Authorization", format!("Bearer {}", data.key)))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Ok);
+        };
+    }
+}
+
+mod rotate_admin_key {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/admins/1/rotate"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let replaces_old_key = |response| {
+            let json = response.into_json::<ApiResponse<AdminKey, ()>>().await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.id, 1);
+
+            let response = client
+                .get(uri!("/api/admin/admins"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Unauthorized);
+
+            let response = client
+                .get(uri!("/api/admin/admins"))
+                .header(Header::new("Authorization", format!("Bearer {}", data.key)))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Ok);
+        };
+    }
+}
+
+mod rotate_nonexistent_admin_key {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/admins/42/rotate"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_404_not_found = |response| {
+            assert_eq!(response.status(), Status::NotFound);
+        };
+    }
+}
+
+mod revoke_admin {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "legacy_admins")]
+        let request = |client| {
+            client
+                .delete(uri!("/api/admin/admins/2"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let rejects_revoked_key = |response| {
+            let json = response.into_json::<ApiResponse<AdminInfo, ()>>().await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.id, 2);
+            assert!(data.revoked_at.is_some());
+
+            let response = client
+                .get(uri!("/api/admin/admins"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer 0f1e2d3c4b5a69788796a5b4c3d2e1f0",
+                ))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Unauthorized);
+        };
+    }
+}
+
+mod revoke_last_admin {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .delete(uri!("/api/admin/admins/1"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod create_scoped_curator {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/admins"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "role": "curator",
+                    "dirnames": ["folder_a"],
+                }))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let new_key_cant_manage_admins = |response| {
+            let json = response.into_json::<ApiResponse<AdminKey, ()>>().await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let response = client
+                .get(uri!("/api/admin/admins"))
+                .header(Header::new("Authorization", format!("Bearer {}", data.key)))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Forbidden);
+        };
+    }
+}
+
+mod update_admin_access {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/admins/3"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "role": "curator",
+                    "dirnames": ["folder_b/"],
+                }))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_new_access = |response| {
+            let json = response.into_json::<ApiResponse<AdminInfo, ()>>().await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.id, 3);
+            assert_eq!(data.role, "curator");
+            assert_eq!(data.dirnames, vec!["folder_b".to_string()]);
+        };
+    }
+}
+
+mod demote_last_owner {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/admins/1"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "role": "viewer",
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod scoped_owner_manages_admins {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/admins"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer c3d4e5f60718293a4b5c6d7e8f90a1b2",
+                ))
+        };
+
+        #[test_request]
+        let returns_403_forbidden = |response| {
+            assert_eq!(response.status(), Status::Forbidden);
+        };
+    }
+}
@@ -0,0 +1,284 @@
+mod common;
+
+use rocket::{
+    fs::relative,
+    http::{
+        uri::Origin,
+        Header,
+        Status,
+    },
+    serde::json::json,
+    uri,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::common::{
+    make_api_test,
+    ApiResponse,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct Comparison {
+    id: Uuid,
+    dirname: String,
+    images: Vec<Origin<'static>>,
+    created_by: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct ComparisonPage {
+    comparisons: Vec<Comparison>,
+    total: i64,
+}
+
+mod viewer_lists_comparisons {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/comparison"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer a1b2c3d4e5f60718293a4b5c6d7e8f90",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+    }
+}
+
+mod viewer_generates_comparisons {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer a1b2c3d4e5f60718293a4b5c6d7e8f90",
+                ))
+        };
+
+        #[test_request]
+        let returns_403_forbidden = |response| {
+            assert_eq!(response.status(), Status::Forbidden);
+        };
+    }
+}
+
+mod viewer_sets_project {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/project?dirname=folder_a"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer a1b2c3d4e5f60718293a4b5c6d7e8f90",
+                ))
+                .json(&json!({
+                    "selection_strategy": "balanced",
+                }))
+        };
+
+        #[test_request]
+        let returns_403_forbidden = |response| {
+            assert_eq!(response.status(), Status::Forbidden);
+        };
+    }
+}
+
+mod scoped_curator_generates_comparisons {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer b2c3d4e5f60718293a4b5c6d7e8f90a1",
+                ))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let generates_only_scoped_dirnames = |response| {
+            let json = response.into_json::<ApiResponse<Vec<Comparison>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.len(), 6);
+            for comparison in data {
+                assert_eq!(comparison.dirname, "folder_a");
+                assert_eq!(comparison.created_by, 4);
+            }
+        };
+    }
+}
+
+mod scoped_curator_reads_other_ranking {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles")]
+        let request = |client| {
+            client
+                .get(uri!("/api/comparison/dirnames/folder_b/ranking"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer b2c3d4e5f60718293a4b5c6d7e8f90a1",
+                ))
+        };
+
+        #[test_request]
+        let returns_403_forbidden = |response| {
+            assert_eq!(response.status(), Status::Forbidden);
+        };
+    }
+}
+
+mod curator_deletes_dirname {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles")]
+        let request = |client| {
+            client
+                .delete(uri!("/api/admin/comparison?dirname=folder_a"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer b2c3d4e5f60718293a4b5c6d7e8f90a1",
+                ))
+        };
+
+        #[test_request]
+        let returns_403_forbidden = |response| {
+            assert_eq!(response.status(), Status::Forbidden);
+        };
+    }
+}
+
+mod scoped_owner_lists_comparisons {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles", "users", "comparisons")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/comparison"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer c3d4e5f60718293a4b5c6d7e8f90a1b2",
+                ))
+        };
+
+        #[test_request]
+        let returns_only_comparisons_of_subfolders = |response| {
+            let json = response.into_json::<ApiResponse<ComparisonPage, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.total, 2);
+            for comparison in data.comparisons {
+                assert_eq!(comparison.dirname, "folder_b/folder_c");
+            }
+        };
+    }
+}
+
+mod scoped_owner_lists_other_dirname {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles", "users", "comparisons")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/comparison?dirname=folder_a"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer c3d4e5f60718293a4b5c6d7e8f90a1b2",
+                ))
+        };
+
+        #[test_request]
+        let returns_403_forbidden = |response| {
+            assert_eq!(response.status(), Status::Forbidden);
+        };
+    }
+}
+
+mod scoped_owner_exports_votes {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "roles", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/votes/export?format=jsonl"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer c3d4e5f60718293a4b5c6d7e8f90a1b2",
+                ))
+        };
+
+        #[test_request]
+        let exports_only_scoped_votes = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            let body = response.into_string().await.expect("body to be present");
+            assert_eq!(body.lines().count(), 1);
+            assert!(body.contains("folder_b/folder_c"));
+        };
+    }
+}
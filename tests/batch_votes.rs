@@ -0,0 +1,193 @@
+mod common;
+
+use rocket::{
+    fs::relative,
+    http::Status,
+    serde::json::{
+        json,
+        Value,
+    },
+    uri,
+};
+use serde::Deserialize;
+
+use crate::common::{
+    make_api_test,
+    ApiResponse,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct Vote {
+    id: i64,
+    vote_value: String,
+    idempotency_key: Option<String>,
+    client_created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchedVoteResult {
+    idempotency_key: String,
+    status: u16,
+    vote: Option<Vote>,
+    error: Option<String>,
+}
+
+fn batch() -> Value {
+    json!([
+        {
+            "idempotency_key": "kiosk-1-0001",
+            "client_created_at": "2026-10-18T09:00:00Z",
+            "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+            "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+            "vote_value": "equal",
+        },
+        {
+            "idempotency_key": "kiosk-1-0002",
+            "client_created_at": "2026-10-18T09:00:05Z",
+            "comparison_id": "44444444-4444-4444-4444-444444444444",
+            "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+            "vote_value": "equal",
+        },
+        {
+            "idempotency_key": "kiosk-1-0003",
+            "client_created_at": "2026-10-18T09:00:10Z",
+            "comparison_id": "33993492-d8ce-4248-a93d-caf88baed82e",
+            "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+            "vote_value": "different",
+        },
+    ])
+}
+
+fn oversized_batch() -> Vec<Value> {
+    (0..501)
+        .map(|i| {
+            json!({
+                "idempotency_key": format!("kiosk-1-{i:04}"),
+                "client_created_at": "2026-10-18T09:00:00Z",
+                "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                "vote_value": "equal",
+            })
+        })
+        .collect()
+}
+
+mod submit_batch {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/votes"))
+                .json(&batch())
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_result_per_vote = |response| {
+            let json = response.into_json::<ApiResponse<Vec<BatchedVoteResult>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let statuses: Vec<u16> = data.iter().map(|result| result.status).collect();
+            assert_eq!(statuses, vec![201, 422, 409]);
+
+            let vote = data[0].vote.as_ref().expect("vote to be present");
+            assert_eq!(vote.vote_value, "equal");
+            assert_eq!(vote.idempotency_key.as_deref(), Some("kiosk-1-0001"));
+            assert!(vote.client_created_at.is_some());
+
+            assert_eq!(data[1].idempotency_key, "kiosk-1-0002");
+            assert_eq!(
+                data[1].error.as_deref(),
+                Some("`comparison` with requested id not found")
+            );
+            assert!(data[2].vote.is_none());
+        };
+
+        #[test_request]
+        let stores_vote_only_once = |response| {
+            let json = response.into_json::<ApiResponse<Vec<BatchedVoteResult>, ()>>()
+                .await;
+            let first_id = json
+                .and_then(|json| json.data)
+                .and_then(|mut data| data.remove(0).vote)
+                .map(|vote| vote.id)
+                .expect("vote to be present");
+
+            let json = client
+                .post(uri!("/api/votes"))
+                .json(&batch())
+                .dispatch()
+                .await
+                .into_json::<ApiResponse<Vec<BatchedVoteResult>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data[0].status, 200);
+            assert_eq!(data[0].vote.as_ref().map(|vote| vote.id), Some(first_id));
+        };
+    }
+}
+
+mod submit_batch_without_client_timestamp {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/votes"))
+                .json(&json!([{
+                    "idempotency_key": "kiosk-1-0001",
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }]))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod submit_oversized_batch {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/votes"))
+                .json(&oversized_batch())
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
@@ -0,0 +1,287 @@
+mod common;
+
+use rocket::{
+    fs::relative,
+    http::{
+        Header,
+        Status,
+    },
+    serde::json::json,
+    uri,
+};
+use serde::Deserialize;
+
+use crate::common::{
+    make_api_test,
+    ApiResponse,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct Vote {
+    id: i64,
+    vote_value: String,
+    retracted_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    votes: u64,
+}
+
+mod change_last_vote {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .put(uri!("/api/vote/43"))
+                .json(&json!({
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_changed_vote = |response| {
+            let json = response.into_json::<ApiResponse<Vote, ()>>().await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.id, 43);
+            assert_eq!(data.vote_value, "equal");
+            assert_eq!(data.retracted_at, None);
+        };
+
+        #[test_request]
+        let keeps_original_vote_value = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            let body = client
+                .get(uri!("/api/admin/votes/export?format=csv"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .dispatch()
+                .await
+                .into_string()
+                .await
+                .expect("body to be present");
+            let record = body
+                .lines()
+                .find(|line| line.starts_with("43,"))
+                .expect("vote 43 to be exported");
+            let fields: Vec<&str> = record.split(',').collect();
+
+            assert_eq!(fields[4], "equal");
+            assert_eq!(
+                fields[8],
+                "/static/images/folder_b/folder_c/image%204.png"
+            );
+        };
+    }
+}
+
+mod change_vote_of_other_user {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .put(uri!("/api/vote/43"))
+                .json(&json!({
+                    "user_id": "ac01a03d-75e3-4244-a33b-a2324b8784f1",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let returns_403_forbidden = |response| {
+            assert_eq!(response.status(), Status::Forbidden);
+        };
+    }
+}
+
+mod change_earlier_vote {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .put(uri!("/api/vote/42"))
+                .json(&json!({
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let returns_409_conflict = |response| {
+            assert_eq!(response.status(), Status::Conflict);
+        };
+    }
+}
+
+mod change_vote_with_incorrect_image {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .put(uri!("/api/vote/43"))
+                .json(&json!({
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "/static/images/image%20A.png",
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod change_vote_after_edit_window {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes", "closed_vote_edit_window")]
+        let request = |client| {
+            client
+                .put(uri!("/api/vote/43"))
+                .json(&json!({
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let returns_409_conflict = |response| {
+            assert_eq!(response.status(), Status::Conflict);
+        };
+    }
+}
+
+mod retract_last_vote {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client.delete(uri!(
+                "/api/vote/43?user_id=3fa85f64-5717-4562-b3fc-2c963f66afa6"
+            ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_retracted_vote = |response| {
+            let json = response.into_json::<ApiResponse<Vote, ()>>().await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.id, 43);
+            assert!(data.retracted_at.is_some());
+        };
+
+        #[test_request]
+        let stops_counting_vote = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            let json = client
+                .get(uri!("/api/user/3fa85f64-5717-4562-b3fc-2c963f66afa6"))
+                .dispatch()
+                .await
+                .into_json::<ApiResponse<User, ()>>()
+                .await;
+            assert_eq!(json.and_then(|json| json.data).map(|user| user.votes), Some(1));
+        };
+
+        #[test_request]
+        let allows_voting_again = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            let response = client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "67d99e8e-6634-4546-8e26-4c9fb95ba81d",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let rejects_retracting_again = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            let response = client
+                .delete(uri!(
+                    "/api/vote/43?user_id=3fa85f64-5717-4562-b3fc-2c963f66afa6"
+                ))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Conflict);
+        };
+    }
+}
+
+mod retract_nonexistent_vote {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client.delete(uri!(
+                "/api/vote/7?user_id=3fa85f64-5717-4562-b3fc-2c963f66afa6"
+            ))
+        };
+
+        #[test_request]
+        let returns_404_not_found = |response| {
+            assert_eq!(response.status(), Status::NotFound);
+        };
+    }
+}
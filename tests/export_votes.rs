@@ -0,0 +1,269 @@
+mod common;
+
+use rocket::{
+    fs::relative,
+    http::{
+        ContentType,
+        Header,
+        Status,
+    },
+    serde::json,
+    uri,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::common::make_api_test;
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct ExportedVote {
+    id: i64,
+    comparison_id: Uuid,
+    dirname: String,
+    images: Vec<String>,
+    vote_value: json::Value,
+    user_id: Uuid,
+    ip_addr: Option<String>,
+    ranking: Vec<Vec<String>>,
+    gold_passed: Option<bool>,
+    user_reliability: Option<f64>,
+}
+
+mod export_votes_as_csv {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/votes/export?format=csv"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_csv_content_type = |response| {
+            assert_eq!(response.content_type(), Some(ContentType::CSV));
+        };
+
+        #[test_request]
+        let returns_header_and_one_record_per_vote = |response| {
+            let body = response.into_string().await.expect("body to be present");
+            let lines: Vec<&str> = body.lines().collect();
+
+            assert_eq!(
+                lines[0],
+                "id,comparison_id,dirname,images,vote_value,user_id,created_at,repetition,\
+                 original_vote_value,retracted_at,response_time_ms,viewport_width,\
+                 viewport_height,device_pixel_ratio,confidence,client_created_at,ranking,\
+                 gold_passed,user_reliability"
+            );
+            assert_eq!(lines.len(), 5);
+        };
+
+        #[test_request]
+        let returns_expected_record = |response| {
+            let body = response.into_string().await.expect("body to be present");
+            let record = body
+                .lines()
+                .find(|line| line.starts_with("42,"))
+                .expect("vote 42 to be exported");
+            let fields: Vec<&str> = record.split(',').collect();
+
+            assert_eq!(fields[1], "33993492-d8ce-4248-a93d-caf88baed82e");
+            assert_eq!(fields[2], "");
+            assert_eq!(
+                fields[3],
+                "/static/images/image%20B.png /static/images/image%20A.png"
+            );
+            assert_eq!(fields[4], "/static/images/image%20A.png");
+            assert_eq!(fields[5], "3fa85f64-5717-4562-b3fc-2c963f66afa6");
+            assert_eq!(fields[7], "0");
+            assert_eq!(fields[8], "/static/images/image%20A.png");
+            assert_eq!(fields[9], "");
+            assert_eq!(fields[10], "");
+            assert_eq!(fields[14], "");
+            assert_eq!(fields[15], "");
+            assert_eq!(
+                fields[16],
+                "/static/images/image%20A.png > /static/images/image%20B.png"
+            );
+            assert_eq!(fields[17], "");
+            assert_eq!(fields[18], "");
+            assert_eq!(fields.len(), 19);
+        };
+    }
+}
+
+mod export_votes_as_jsonl_for_dirname_with_ip_addr {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!(
+                    "/api/admin/votes/export?format=jsonl&dirname=folder_b/folder_c&ip_addr=true"
+                ))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_only_votes_of_dirname = |response| {
+            let body = response.into_string().await.expect("body to be present");
+            let votes: Vec<ExportedVote> = body
+                .lines()
+                .map(|line| json::from_str(line).expect("line to be json"))
+                .collect();
+
+            assert_eq!(votes.len(), 1);
+            assert_eq!(votes[0].dirname, "folder_b/folder_c");
+            assert_eq!(
+                votes[0].vote_value,
+                "/static/images/folder_b/folder_c/image%204.png"
+            );
+            assert_eq!(votes[0].images.len(), 2);
+            assert_eq!(votes[0].ranking, vec![
+                vec!["/static/images/folder_b/folder_c/image%204.png"],
+                vec!["/static/images/folder_b/folder_c/image%205.png"],
+            ]);
+        };
+
+        #[test_request]
+        let returns_ip_addr = |response| {
+            let body = response.into_string().await.expect("body to be present");
+            let vote: ExportedVote = json::from_str(
+                body.lines().next().expect("a line to be present")
+            )
+            .expect("line to be json");
+
+            assert_eq!(vote.ip_addr, Some("127.0.0.1".to_string()));
+        };
+    }
+}
+
+mod export_votes_of_tuple_as_jsonl {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "tuple_comparisons")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/votes/export?format=jsonl&dirname=folder_a"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_ranking_in_tiers = |response| {
+            let body = response.into_string().await.expect("body to be present");
+            let vote: ExportedVote = json::from_str(
+                body.lines().next().expect("a line to be present")
+            )
+            .expect("line to be json");
+
+            assert_eq!(
+                vote.vote_value["best"],
+                "/static/images/folder_a/image%202.png"
+            );
+            assert_eq!(vote.ranking, vec![
+                vec!["/static/images/folder_a/image%202.png"],
+                vec!["/static/images/folder_a/image%203.png"],
+                vec!["/static/images/folder_a/image%201.png"],
+            ]);
+        };
+    }
+}
+
+mod export_votes_unauthorized {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/votes/export?format=csv"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer c3e3a2f7a4bb2f9d1a470660c6d68b09",
+                ))
+        };
+
+        #[test_request]
+        let returns_401_unauthorized = |response| {
+            assert_eq!(response.status(), Status::Unauthorized);
+        };
+    }
+}
+
+mod export_votes_with_gold_comparisons_as_jsonl {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes", "gold_comparisons")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/votes/export?format=jsonl"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_gold_passed_and_user_reliability = |response| {
+            let body = response.into_string().await.expect("body to be present");
+            let votes: Vec<ExportedVote> = body
+                .lines()
+                .map(|line| json::from_str(line).expect("line to be json"))
+                .collect();
+            let gold_vote = votes
+                .iter()
+                .find(|vote| {
+                    vote.comparison_id.to_string()
+                        == "9b8a7c6d-5e4f-4031-8a2b-1c0d9e8f7a6b"
+                })
+                .expect("gold vote to be exported");
+            let other_vote = votes
+                .iter()
+                .find(|vote| vote.id == 42)
+                .expect("vote 42 to be exported");
+
+            assert_eq!(gold_vote.gold_passed, Some(false));
+            assert_eq!(gold_vote.user_reliability, Some(0.0));
+            assert_eq!(other_vote.gold_passed, None);
+            assert_eq!(other_vote.user_reliability, None);
+        };
+    }
+}
@@ -23,6 +23,12 @@ struct Comparison {
     created_by: i64,
 }
 
+#[derive(Debug, PartialEq, Deserialize)]
+struct DirnameError {
+    dirname: String,
+    error: String,
+}
+
 impl PartialOrd for Comparison {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         let as_string =
@@ -257,7 +263,7 @@ mod generate_comparisons_from_folder_error {
 
         #[test_request]
         let returns_json_err = |response| {
-            let json = response.into_json::<ApiResponse<(), String>>()
+            let json = response.into_json::<ApiResponse<(), Vec<DirnameError>>>()
                 .await;
 
             assert!(json.is_some());
@@ -265,17 +271,21 @@ mod generate_comparisons_from_folder_error {
 
         #[test_request]
         let returns_expected_error = |response| {
-            let json = response.into_json::<ApiResponse<(), String>>()
+            let json = response.into_json::<ApiResponse<(), Vec<DirnameError>>>()
                 .await;
             let error = json
                 .expect("json to be present")
                 .error
                 .expect("error to be present");
 
-            assert_eq!(
-                error,
-                "Not enough files in STATIC_DIR/folder_b (minimum 2 needed)"
-            );
+            let expected_error = vec![DirnameError {
+                dirname: "folder_b".to_string(),
+                error: "Not enough files in STATIC_DIR/folder_b (minimum 2 \
+                        needed)"
+                    .to_string(),
+            }];
+
+            assert_eq!(error, expected_error);
         };
     }
 }
@@ -330,3 +340,679 @@ mod generate_comparisons_unauthorized {
         };
     }
 }
+
+mod generate_comparisons_with_reference_pairing {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison?pairing=reference"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let returns_comparisons_against_first_image = |response| {
+            let json = response.into_json::<ApiResponse<Vec<Comparison>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let references: Vec<Origin> = vec![
+                uri!("/static/images/image%20A.png"),
+                uri!("/static/images/folder_a/image%201.png"),
+                uri!("/static/images/folder_b/folder_c/image%204.png"),
+                uri!("/static/images/folder_d_truncate_at_2/image%201.png"),
+            ];
+
+            // (n - 1) pairs per dirname, in both orders
+            assert_eq!(data.len(), 2 * (1 + 2 + 1 + 3));
+            for comparison in data {
+                assert!(comparison
+                    .images
+                    .iter()
+                    .any(|image| references.contains(image)));
+            }
+        };
+    }
+}
+
+mod generate_comparisons_with_ratings {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison?pairing=reference&ratings=true"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let returns_a_single_image_comparison_per_image = |response| {
+            let json = response.into_json::<ApiResponse<Vec<Comparison>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let single_images = data
+                .iter()
+                .filter(|comparison| comparison.images.len() == 1)
+                .count();
+
+            // one per image, besides the (n - 1) pairs per dirname in both
+            // orders
+            assert_eq!(single_images, 2 + 3 + 2 + 4);
+            assert_eq!(data.len(), 2 * (1 + 2 + 1 + 3) + single_images);
+        };
+    }
+}
+
+mod generate_comparisons_with_invalid_pairing {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison?pairing=alphabetical"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod generate_comparisons_with_manifests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct Project {
+        selection_strategy: String,
+        prompt: Option<String>,
+    }
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/manifest"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison?pairing=round_robin"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let returns_comparisons_from_manifests = |response| {
+            let json = response.into_json::<ApiResponse<Vec<Comparison>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let images_of = |dirname: &str| -> Vec<Vec<Origin>> {
+                data.iter()
+                    .filter(|comparison| comparison.dirname == dirname)
+                    .map(|comparison| comparison.images.clone())
+                    .collect()
+            };
+
+            // pairing from the query, without a manifest
+            assert_eq!(images_of("").len(), 2);
+            // reference pairing in explicit order, without reversed pairs
+            assert_eq!(
+                images_of("reference_set"),
+                vec![
+                    vec![
+                        uri!("/static/images/reference_set/image%203.png"),
+                        uri!("/static/images/reference_set/image%201.png"),
+                    ],
+                    vec![
+                        uri!("/static/images/reference_set/image%203.png"),
+                        uri!("/static/images/reference_set/image%202.png"),
+                    ],
+                ]
+            );
+            // windowed pairing by name descending, with reversed pairs
+            assert_eq!(
+                images_of("json_set"),
+                vec![
+                    vec![
+                        uri!("/static/images/json_set/image%203.png"),
+                        uri!("/static/images/json_set/image%202.png"),
+                    ],
+                    vec![
+                        uri!("/static/images/json_set/image%202.png"),
+                        uri!("/static/images/json_set/image%203.png"),
+                    ],
+                    vec![
+                        uri!("/static/images/json_set/image%202.png"),
+                        uri!("/static/images/json_set/image%201.png"),
+                    ],
+                    vec![
+                        uri!("/static/images/json_set/image%201.png"),
+                        uri!("/static/images/json_set/image%202.png"),
+                    ],
+                ]
+            );
+            // consecutive triplets, without rotations
+            assert_eq!(
+                images_of("triplet_set"),
+                vec![
+                    vec![
+                        uri!("/static/images/triplet_set/image%201.png"),
+                        uri!("/static/images/triplet_set/image%202.png"),
+                        uri!("/static/images/triplet_set/image%203.png"),
+                    ],
+                    vec![
+                        uri!("/static/images/triplet_set/image%202.png"),
+                        uri!("/static/images/triplet_set/image%203.png"),
+                        uri!("/static/images/triplet_set/image%204.png"),
+                    ],
+                ]
+            );
+        };
+
+        #[test_request]
+        let saves_project_settings_from_manifest = |response| {
+            assert_eq!(response.status(), Status::Created);
+
+            let response = client
+                .get(uri!("/api/admin/project?dirname=reference_set"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .dispatch()
+                .await;
+            let json = response.into_json::<ApiResponse<Project, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.selection_strategy, "balanced");
+            assert_eq!(data.prompt.as_deref(), Some("Which image is sharper?"));
+        };
+    }
+}
+
+mod generate_comparisons_with_tuple_size {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison?tuple_size=3&transaction=dirname"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let returns_rotated_triplets = |response| {
+            let json = response.into_json::<ApiResponse<GenerationReport, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let failed: Vec<&str> = data
+                .failed
+                .iter()
+                .map(|failed| failed.dirname.as_str())
+                .collect();
+
+            // folder_a (123, 231, 312) and folder_d_truncate_at_2 (two
+            // windows of three, each rotated)
+            assert_eq!(data.created.len(), 3 + 2 * 3);
+            assert_eq!(failed, vec!["", "folder_b/folder_c"]);
+            assert_eq!(
+                data.failed[0].error,
+                "Not enough files in STATIC_DIR/ (minimum 3 needed)"
+            );
+        };
+    }
+}
+
+mod generate_comparisons_with_tuple_size_and_other_pairing {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison?tuple_size=3&pairing=swiss"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_500_internal_server_error = |response| {
+            assert_eq!(response.status(), Status::InternalServerError);
+        };
+    }
+}
+
+mod generate_comparisons_with_invalid_manifest {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/manifest_error"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_500_internal_server_error = |response| {
+            assert_eq!(response.status(), Status::InternalServerError);
+        };
+
+        #[test_request]
+        let returns_expected_error = |response| {
+            let json = response.into_json::<ApiResponse<(), Vec<DirnameError>>>()
+                .await;
+            let error = json
+                .expect("json to be present")
+                .error
+                .expect("error to be present");
+
+            assert_eq!(error.len(), 1);
+            assert_eq!(error[0].dirname, "folder_a");
+            assert!(error[0].error.starts_with(
+                "Invalid manifest in STATIC_DIR/folder_a: manifest.toml:"
+            ));
+        };
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GenerationReport {
+    created: Vec<Comparison>,
+    existing: Vec<Comparison>,
+    retired: Vec<Comparison>,
+    failed: Vec<DirnameError>,
+}
+
+mod generate_comparisons_incrementally {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "comparisons", "partial_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison?incremental=true"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let creates_only_pairs_with_new_images = |response| {
+            let json = response.into_json::<ApiResponse<GenerationReport, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let new_image: Origin = uri!("/static/images/folder_a/image%203.png");
+            let created_in_folder_a: Vec<&Comparison> = data
+                .created
+                .iter()
+                .filter(|comparison| comparison.dirname == "folder_a")
+                .collect();
+
+            assert_eq!(created_in_folder_a.len(), 4);
+            for comparison in created_in_folder_a {
+                assert!(comparison.images.contains(&new_image));
+            }
+            // all of folder_d_truncate_at_2 is new
+            assert_eq!(data.created.len(), 4 + 6);
+        };
+
+        #[test_request]
+        let reports_existing_comparisons = |response| {
+            let json = response.into_json::<ApiResponse<GenerationReport, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.existing.len(), 7);
+            assert_eq!(data.retired.len(), 0);
+        };
+    }
+}
+
+mod generate_comparisons_retiring_missing_images {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "partial_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!(
+                    "/api/admin/comparison?incremental=true&retire_missing=true"
+                ))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let retires_comparisons_with_missing_images = |response| {
+            let json = response.into_json::<ApiResponse<GenerationReport, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.retired.len(), 1);
+            assert_eq!(
+                data.retired[0].images[1],
+                uri!("/static/images/folder_a/image%209.png")
+            );
+            assert_eq!(data.existing.len(), 6);
+            assert_eq!(data.created.len(), 4 + 6);
+        };
+
+        #[test_request]
+        let does_not_hand_out_retired_comparisons = |response| {
+            assert_eq!(response.status(), Status::Created);
+
+            for _ in 0..6 {
+                let response = client
+                    .get(uri!(
+                        "/api/user/3fa85f64-5717-4562-b3fc-2c963f66afa6/comparison?dirname=folder_a"
+                    ))
+                    .dispatch()
+                    .await;
+                let json = response.into_json::<ApiResponse<Comparison, ()>>()
+                    .await;
+                let data = json
+                    .expect("json to be preset")
+                    .data
+                    .expect("data to be present");
+
+                assert!(!data
+                    .images
+                    .contains(&uri!("/static/images/folder_a/image%209.png")));
+            }
+        };
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DirnamePreview {
+    dirname: String,
+    images: usize,
+    created: usize,
+    pairs: Vec<Vec<Origin<'static>>>,
+    error: Option<String>,
+}
+
+mod generate_comparisons_dry_run {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/error"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison?dry_run=true"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_preview_per_dirname = |response| {
+            let json = response.into_json::<ApiResponse<Vec<DirnamePreview>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let dirnames: Vec<&str> =
+                data.iter().map(|preview| preview.dirname.as_str()).collect();
+            assert_eq!(dirnames, vec!["", "folder_a", "folder_b"]);
+
+            assert_eq!(data[1].images, 3);
+            assert_eq!(data[1].created, 6);
+            assert_eq!(data[1].pairs.len(), 6);
+            assert_eq!(
+                data[1].pairs[0],
+                vec![
+                    uri!("/static/images/folder_a/image%201.png"),
+                    uri!("/static/images/folder_a/image%202.png"),
+                ]
+            );
+            assert_eq!(data[1].error, None);
+        };
+
+        #[test_request]
+        let returns_validation_errors = |response| {
+            let json = response.into_json::<ApiResponse<Vec<DirnamePreview>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data[2].created, 0);
+            assert_eq!(
+                data[2].error.as_deref(),
+                Some("Not enough files in STATIC_DIR/folder_b (minimum 2 needed)")
+            );
+        };
+
+        #[test_request]
+        let writes_nothing = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            let response = client
+                .get(uri!("/api/comparison/dirnames"))
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::ServiceUnavailable);
+        };
+    }
+}
+
+mod generate_comparisons_from_folder_error_writes_nothing {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/error"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let leaves_no_comparisons_behind = |response| {
+            assert_eq!(response.status(), Status::InternalServerError);
+
+            let response = client
+                .get(uri!("/api/comparison/dirnames"))
+                .dispatch()
+                .await;
+
+            assert_eq!(response.status(), Status::ServiceUnavailable);
+        };
+    }
+}
+
+mod generate_comparisons_with_transaction_per_dirname {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/error"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison?transaction=dirname"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let generates_dirnames_without_errors = |response| {
+            let json = response.into_json::<ApiResponse<GenerationReport, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let expected_failed = vec![DirnameError {
+                dirname: "folder_b".to_string(),
+                error: "Not enough files in STATIC_DIR/folder_b (minimum 2 \
+                        needed)"
+                    .to_string(),
+            }];
+
+            // root (AB, BA) and folder_a (12, 21, 13, 31, 23, 32)
+            assert_eq!(data.created.len(), 2 + 6);
+            assert_eq!(data.failed, expected_failed);
+        };
+    }
+}
+
+mod generate_comparisons_with_invalid_transaction {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins")]
+        let request = |client| {
+            client
+                .post(uri!("/api/admin/comparison?transaction=none"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
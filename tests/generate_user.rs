@@ -2,7 +2,10 @@ mod common;
 
 use rocket::{
     fs::relative,
-    http::Status,
+    http::{
+        Header,
+        Status,
+    },
     uri,
 };
 use serde::Deserialize;
@@ -101,3 +104,102 @@ mod generate_user {
         };
     }
 }
+
+mod generate_user_with_repeated_idempotency_key {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures()]
+        let request = |client| {
+            client
+                .post(uri!("/api/user"))
+                .header(Header::new("Idempotency-Key", "6f1c2a9e-3b7d-4e58-9a0c-1d2e3f4a5b6c"))
+        };
+
+        #[test_request]
+        let returns_original_response = |response| {
+            assert_eq!(response.status(), Status::Created);
+            let body = response.into_string().await.expect("body to be present");
+
+            let response = client
+                .post(uri!("/api/user"))
+                .header(Header::new("Idempotency-Key", "6f1c2a9e-3b7d-4e58-9a0c-1d2e3f4a5b6c"))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Created);
+            assert_eq!(
+                response.into_string().await.expect("body to be present"),
+                body
+            );
+        };
+
+        #[test_request]
+        let creates_user_for_other_key = |response| {
+            let json = response.into_json::<ApiResponse<User, ()>>().await;
+            let id = json.and_then(|json| json.data).map(|user| user.id);
+
+            let json = client
+                .post(uri!("/api/user"))
+                .header(Header::new("Idempotency-Key", "0b9e8d7c-6a5f-4e3d-8c2b-1a0f9e8d7c6b"))
+                .dispatch()
+                .await
+                .into_json::<ApiResponse<User, ()>>()
+                .await;
+            let other_id = json.and_then(|json| json.data).map(|user| user.id);
+
+            assert!(id.is_some());
+            assert_ne!(id, other_id);
+        };
+    }
+}
+
+mod generate_user_with_empty_idempotency_key {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures()]
+        let request = |client| {
+            client
+                .post(uri!("/api/user"))
+                .header(Header::new("Idempotency-Key", ""))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod generate_user_with_idempotency_key_not_a_uuid {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures()]
+        let request = |client| {
+            client
+                .post(uri!("/api/user"))
+                .header(Header::new("Idempotency-Key", "signup-0001"))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+
+        #[test_request]
+        let returns_expected_error = |response| {
+            let json = response.into_json::<ApiResponse<(), String>>()
+                .await;
+            let error = json
+                .expect("json to be preset")
+                .error
+                .expect("error to be present");
+
+            assert_eq!(error, "`Idempotency-Key` must be a UUID");
+        };
+    }
+}
@@ -21,6 +21,14 @@ struct Comparison {
     id: Uuid,
     dirname: String,
     images: Vec<Origin<'static>>,
+    question: Question,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Question {
+    prompt: Option<String>,
+    answers: Vec<String>,
+    rating_scale: i64,
 }
 
 mod get_comparison_for_user_with_correct_id_on_root {
@@ -102,6 +110,40 @@ mod get_comparison_for_user_with_correct_id_on_root {
     }
 }
 
+mod get_comparison_for_user_with_question {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "answers")]
+        let request = |client| {
+            client.get(uri!(
+                "/api/user/3fa85f64-5717-4562-b3fc-2c963f66afa6/comparison"
+            ))
+        };
+
+        #[test_request]
+        let returns_question_of_project = |response| {
+            let json = response.into_json::<ApiResponse<Comparison, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let expected_question = Question {
+                prompt: Some("Which image is sharper?".to_string()),
+                answers: vec!["equal".to_string(), "cannot_tell".to_string()],
+                rating_scale: 7,
+            };
+
+            assert_eq!(data.question, expected_question);
+        };
+    }
+}
+
 mod get_comparison_for_user_with_correct_id_on_subfolder {
     use super::*;
 
@@ -298,3 +340,132 @@ mod get_comparison_for_user_when_none_available {
         };
     }
 }
+
+mod get_comparison_for_user_with_balanced_selection {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct User {
+        id: Uuid,
+    }
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes", "projects")]
+        let request = |client| {
+            client.post(uri!("/api/user"))
+        };
+
+        #[test_request]
+        let returns_comparison_with_fewest_votes = |response| {
+            let json = response.into_json::<ApiResponse<User, ()>>()
+                .await;
+            let user = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            for _ in 0..10 {
+                let response = client
+                    .get(format!("/api/user/{}/comparison", user.id))
+                    .dispatch()
+                    .await;
+                let json = response.into_json::<ApiResponse<Comparison, ()>>()
+                    .await;
+                let data = json
+                    .expect("json to be preset")
+                    .data
+                    .expect("data to be present");
+
+                assert_eq!(
+                    data.id.to_string(),
+                    "7d68f7e3-afe5-4d08-9d89-e6905f152eec"
+                );
+            }
+        };
+    }
+}
+
+mod get_comparison_for_user_with_informative_selection {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct User {
+        id: Uuid,
+    }
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes", "ranked_comparisons")]
+        let request = |client| {
+            client.post(uri!("/api/user"))
+        };
+
+        #[test_request]
+        let returns_comparison_with_most_uncertain_pair = |response| {
+            let json = response.into_json::<ApiResponse<User, ()>>()
+                .await;
+            let user = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            for _ in 0..10 {
+                let response = client
+                    .get(format!(
+                        "/api/user/{}/comparison?dirname=folder_a",
+                        user.id
+                    ))
+                    .dispatch()
+                    .await;
+                let json = response.into_json::<ApiResponse<Comparison, ()>>()
+                    .await;
+                let data = json
+                    .expect("json to be preset")
+                    .data
+                    .expect("data to be present");
+
+                assert_eq!(
+                    data.id.to_string(),
+                    "2b1c5c4e-0a7d-4c1f-9a3e-6d2f8b7a9c01"
+                );
+            }
+        };
+    }
+}
+
+mod get_comparison_for_user_with_gold_comparisons {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes", "gold_comparisons")]
+        let request = |client| {
+            client.get(uri!("/api/user/3fa85f64-5717-4562-b3fc-2c963f66afa6/comparison"))
+        };
+
+        #[test_request]
+        let returns_gold_comparison_at_gold_rate = |response| {
+            let json = response.into_json::<ApiResponse<Comparison, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(
+                data.id.to_string(),
+                "9b8a7c6d-5e4f-4031-8a2b-1c0d9e8f7a6b"
+            );
+        };
+
+        #[test_request]
+        let does_not_reveal_expected_answer = |response| {
+            let json = response.into_json::<rocket::serde::json::Value>()
+                .await
+                .expect("json to be preset");
+
+            assert!(json["data"].get("expected_vote_value").is_none());
+        };
+    }
+}
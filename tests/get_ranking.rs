@@ -0,0 +1,313 @@
+mod common;
+
+use rocket::{
+    fs::relative,
+    http::{
+        uri::Origin,
+        Header,
+        Status,
+    },
+    uri,
+};
+use serde::Deserialize;
+
+use crate::common::{
+    make_api_test,
+    ApiResponse,
+};
+
+#[derive(Debug, Deserialize)]
+struct Score {
+    image: Origin<'static>,
+    score: f64,
+    standard_error: f64,
+    mean_rating: Option<f64>,
+    ratings: i64,
+}
+
+mod get_ranking_for_existing_dirname {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get("/api/comparison/dirnames/folder_b%2Ffolder_c/ranking")
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_json_ok = |response| {
+            let json = response.into_json::<ApiResponse<Vec<Score>, ()>>()
+                .await;
+
+            assert!(json.is_some());
+        };
+
+        #[test_request]
+        let returns_images_ordered_by_score = |response| {
+            let json = response.into_json::<ApiResponse<Vec<Score>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let images: Vec<Origin> =
+                data.iter().map(|score| score.image.clone()).collect();
+            let expected_images: Vec<Origin> = vec![
+                uri!("/static/images/folder_b/folder_c/image%204.png"),
+                uri!("/static/images/folder_b/folder_c/image%205.png"),
+            ];
+
+            assert_eq!(images, expected_images);
+            assert!(data[0].score > data[1].score);
+        };
+
+        #[test_request]
+        let returns_finite_standard_errors = |response| {
+            let json = response.into_json::<ApiResponse<Vec<Score>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            for score in data {
+                assert!(score.standard_error.is_finite());
+                assert!(score.standard_error > 0.0);
+            }
+        };
+    }
+}
+
+mod get_ranking_with_ratings {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes", "rating_comparisons")]
+        let request = |client| {
+            client
+                .get("/api/comparison/dirnames/folder_b%2Ffolder_c/ranking")
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_mean_rating_of_rated_images = |response| {
+            let json = response.into_json::<ApiResponse<Vec<Score>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let ratings: Vec<(Option<f64>, i64)> = data
+                .iter()
+                .map(|score| (score.mean_rating, score.ratings))
+                .collect();
+
+            assert_eq!(ratings, vec![(Some(4.0), 1), (None, 0)]);
+        };
+    }
+}
+
+mod get_ranking_with_retired_comparisons {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes", "retired_comparisons")]
+        let request = |client| {
+            client
+                .get("/api/comparison/dirnames/folder_b%2Ffolder_c/ranking")
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let leaves_out_images_of_retired_comparisons = |response| {
+            let json = response.into_json::<ApiResponse<Vec<Score>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let images: Vec<Origin> =
+                data.iter().map(|score| score.image.clone()).collect();
+            let expected_images: Vec<Origin> = vec![
+                uri!("/static/images/folder_b/folder_c/image%204.png"),
+                uri!("/static/images/folder_b/folder_c/image%205.png"),
+            ];
+
+            assert_eq!(images, expected_images);
+        };
+    }
+}
+
+mod get_ranking_for_root_dirname {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/comparison/ranking"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_images_of_root_dirname_ordered_by_score = |response| {
+            let json = response.into_json::<ApiResponse<Vec<Score>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let images: Vec<Origin> =
+                data.iter().map(|score| score.image.clone()).collect();
+            let expected_images: Vec<Origin> = vec![
+                uri!("/static/images/image%20A.png"),
+                uri!("/static/images/image%20B.png"),
+            ];
+
+            assert_eq!(images, expected_images);
+        };
+    }
+}
+
+mod get_ranking_for_nested_dirname_in_query {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get("/api/comparison/ranking?dirname=folder_b/folder_c")
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_images_of_dirname = |response| {
+            let json = response.into_json::<ApiResponse<Vec<Score>, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let images: Vec<Origin> =
+                data.iter().map(|score| score.image.clone()).collect();
+            let expected_images: Vec<Origin> = vec![
+                uri!("/static/images/folder_b/folder_c/image%204.png"),
+                uri!("/static/images/folder_b/folder_c/image%205.png"),
+            ];
+
+            assert_eq!(images, expected_images);
+        };
+    }
+}
+
+mod get_ranking_for_nonexistent_dirname {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/comparison/dirnames/nonexistent/ranking"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_404_not_found = |response| {
+            assert_eq!(response.status(), Status::NotFound);
+        };
+
+        #[test_request]
+        let returns_expected_error = |response| {
+            let json = response.into_json::<ApiResponse<(), String>>()
+                .await;
+            let error = json
+                .expect("json to be preset")
+                .error
+                .expect("error to be present");
+
+            assert_eq!(error, "No `comparison`s found for requested `dirname`");
+        };
+    }
+}
+
+mod get_ranking_unauthorized {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .get(uri!("/api/comparison/dirnames/folder_a/ranking"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer c3e3a2f7a4bb2f9d1a470660c6d68b09",
+                ))
+        };
+
+        #[test_request]
+        let returns_401_unauthorized = |response| {
+            assert_eq!(response.status(), Status::Unauthorized);
+        };
+
+        #[test_request]
+        let returns_expected_header = |response| {
+            let www_authenticate = response
+                .headers()
+                .get_one("WWW-Authenticate");
+
+            assert_eq!(www_authenticate, Some("Bearer"));
+        };
+    }
+}
@@ -21,6 +21,7 @@ struct User {
     id: Uuid,
     votes: u64,
     average_lambda: f64,
+    reliability: Option<f64>,
 }
 
 mod get_user_with_correct_id {
@@ -59,6 +60,7 @@ mod get_user_with_correct_id {
                 id: uuid!("3fa85f64-5717-4562-b3fc-2c963f66afa6"),
                 votes: 2,
                 average_lambda: 0.1234,
+                reliability: None,
             };
 
             assert_eq!(data, expected_user);
@@ -142,3 +144,28 @@ mod get_user_with_incorrect_parameter {
         };
     }
 }
+
+mod get_user_with_gold_votes {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("users", "admins", "comparisons", "votes", "gold_comparisons")]
+        let request = |client| {
+            client.get(uri!("/api/user/ac01a03d-75e3-4244-a33b-a2324b8784f1"))
+        };
+
+        #[test_request]
+        let returns_reliability = |response| {
+            let json = response.into_json::<ApiResponse<User, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.votes, 3);
+            assert_eq!(data.reliability, Some(0.0));
+        };
+    }
+}
@@ -35,7 +35,7 @@ macro_rules! make_api_preflight_test {
                     let allow_methods =
                         response.headers().get_one("Access-Control-Allow-Methods");
 
-                    assert_eq!(allow_methods, Some("OPTIONS, POST, DELETE, GET"));
+                    assert_eq!(allow_methods, Some("OPTIONS, POST, PUT, DELETE, GET"));
                 };
 
                 #[test_request]
@@ -43,7 +43,7 @@ macro_rules! make_api_preflight_test {
                     let allow_headers =
                         response.headers().get_one("Access-Control-Allow-Headers");
 
-                    assert_eq!(allow_headers, Some("Content-Type, Authorization"));
+                    assert_eq!(allow_headers, Some("Content-Type, Authorization, Idempotency-Key"));
                 };
             }
         }
@@ -0,0 +1,353 @@
+mod common;
+
+use rocket::{
+    fs::relative,
+    http::{
+        Header,
+        Status,
+    },
+    serde::json::json,
+    uri,
+};
+use serde::Deserialize;
+
+use crate::common::{
+    make_api_test,
+    ApiResponse,
+};
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Project {
+    dirname: String,
+    selection_strategy: String,
+    allow_repeat_votes: bool,
+    rating_scale: i64,
+    rating_labels: Vec<String>,
+    answers: Vec<String>,
+    gold_rate: f64,
+}
+
+mod get_project_without_settings {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "comparisons", "projects")]
+        let request = |client| {
+            client
+                .get(uri!("/api/admin/project?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let returns_default_project = |response| {
+            let json = response.into_json::<ApiResponse<Project, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let expected_project = Project {
+                dirname: "folder_b/folder_c".to_string(),
+                selection_strategy: "random".to_string(),
+                allow_repeat_votes: false,
+                rating_scale: 7,
+                rating_labels: Vec::new(),
+                answers: vec!["equal".to_string(), "different".to_string()],
+                gold_rate: 0.0,
+            };
+
+            assert_eq!(data, expected_project);
+        };
+    }
+}
+
+mod set_project_with_correct_parameters {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "comparisons", "projects")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/project?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "selection_strategy": "balanced",
+                    "allow_repeat_votes": true,
+                    "gold_rate": 0.25,
+                }))
+        };
+
+        #[test_request]
+        let returns_200_ok = |response| {
+            assert_eq!(response.status(), Status::Ok);
+        };
+
+        #[test_request]
+        let persists_project = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            let response = client
+                .get(uri!("/api/admin/project?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .dispatch()
+                .await;
+            let json = response.into_json::<ApiResponse<Project, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            let expected_project = Project {
+                dirname: "folder_b/folder_c".to_string(),
+                selection_strategy: "balanced".to_string(),
+                allow_repeat_votes: true,
+                rating_scale: 7,
+                rating_labels: Vec::new(),
+                answers: vec!["equal".to_string(), "different".to_string()],
+                gold_rate: 0.25,
+            };
+
+            assert_eq!(data, expected_project);
+        };
+    }
+}
+
+mod set_project_keeping_omitted_settings {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "comparisons", "answers")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/project"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "allow_repeat_votes": true,
+                }))
+        };
+
+        #[test_request]
+        let returns_project_with_other_settings_unchanged = |response| {
+            let json = response.into_json::<ApiResponse<Project, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert!(data.allow_repeat_votes);
+            assert_eq!(data.answers, vec!["equal".to_string(), "cannot_tell".to_string()]);
+        };
+    }
+}
+
+mod set_project_with_incorrect_strategy {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "comparisons", "projects")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/project?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "selection_strategy": "alphabetical",
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod set_project_with_negative_vote_edit_window {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "comparisons", "projects")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/project?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "vote_edit_window": -1,
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod set_project_with_rating_scale {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "comparisons", "projects")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/project?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "rating_scale": 3,
+                    "rating_labels": ["bad", "fair", "good"],
+                }))
+        };
+
+        #[test_request]
+        let returns_project_with_rating_scale = |response| {
+            let json = response.into_json::<ApiResponse<Project, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.rating_scale, 3);
+            assert_eq!(data.rating_labels, vec!["bad", "fair", "good"]);
+        };
+    }
+}
+
+mod set_project_with_rating_labels_not_matching_scale {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "comparisons", "projects")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/project?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "rating_scale": 5,
+                    "rating_labels": ["bad", "good"],
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod set_project_with_unknown_answer {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "comparisons", "projects")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/project?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "answers": ["equal", "maybe"],
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod set_project_unauthorized {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "comparisons", "projects")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/project?dirname=folder_b/folder_c"))
+                .json(&json!({
+                    "selection_strategy": "balanced",
+                }))
+        };
+
+        #[test_request]
+        let returns_401_unauthorized = |response| {
+            assert_eq!(response.status(), Status::Unauthorized);
+        };
+    }
+}
+
+mod set_project_with_gold_rate_above_one {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "comparisons", "projects")]
+        let request = |client| {
+            client
+                .put(uri!("/api/admin/project?dirname=folder_b/folder_c"))
+                .header(Header::new(
+                    "Authorization",
+                    "Bearer ef8a53f0b0cb43dd764fe16a442752d6",
+                ))
+                .json(&json!({
+                    "gold_rate": 1.5,
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
@@ -1,6 +1,9 @@
 mod common;
 
-use std::net::IpAddr;
+use std::{
+    net::IpAddr,
+    time::Duration,
+};
 
 use chrono::{
     DateTime,
@@ -8,7 +11,10 @@ use chrono::{
 };
 use rocket::{
     fs::relative,
-    http::Status,
+    http::{
+        Header,
+        Status,
+    },
     serde::{
         json::json,
         uuid::{
@@ -35,6 +41,40 @@ struct Vote {
     ip_addr: IpAddr,
 }
 
+#[derive(Debug, Deserialize)]
+struct RepeatedVote {
+    repetition: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TimedVote {
+    response_time_ms: Option<i64>,
+    viewport_width: Option<i64>,
+    viewport_height: Option<i64>,
+    device_pixel_ratio: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RatedVote {
+    confidence: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RankedVote {
+    vote_value: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RatingVote {
+    vote_value: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    average_lambda: f64,
+    reliability: Option<f64>,
+}
+
 mod vote_with_correct_parameters_and_vote_value_is_equal {
     use super::*;
 
@@ -46,7 +86,7 @@ mod vote_with_correct_parameters_and_vote_value_is_equal {
                 .post(uri!("/api/vote"))
                 .remote("127.0.0.1:80".parse().unwrap())
                 .json(&json!({
-                    "comparison_id": "33993492-d8ce-4248-a93d-caf88baed82e",
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
                     "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
                     "vote_value": "equal",
                 }))
@@ -76,7 +116,7 @@ mod vote_with_correct_parameters_and_vote_value_is_equal {
 
             let expected_created_vote = Vote {
                 id: data.id,
-                comparison_id: uuid!("33993492-d8ce-4248-a93d-caf88baed82e"),
+                comparison_id: uuid!("7d68f7e3-afe5-4d08-9d89-e6905f152eec"),
                 user_id: uuid!("3fa85f64-5717-4562-b3fc-2c963f66afa6"),
                 vote_value: "equal".to_string(),
                 created_at: data.created_at,
@@ -111,7 +151,7 @@ mod vote_with_correct_parameters_and_vote_value_is_different {
                 .post(uri!("/api/vote"))
                 .remote("127.0.0.1:80".parse().unwrap())
                 .json(&json!({
-                    "comparison_id": "33993492-d8ce-4248-a93d-caf88baed82e",
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
                     "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
                     "vote_value": "different",
                 }))
@@ -141,7 +181,7 @@ mod vote_with_correct_parameters_and_vote_value_is_different {
 
             let expected_created_vote = Vote {
                 id: data.id,
-                comparison_id: uuid!("33993492-d8ce-4248-a93d-caf88baed82e"),
+                comparison_id: uuid!("7d68f7e3-afe5-4d08-9d89-e6905f152eec"),
                 user_id: uuid!("3fa85f64-5717-4562-b3fc-2c963f66afa6"),
                 vote_value: "different".to_string(),
                 created_at: data.created_at,
@@ -176,7 +216,7 @@ mod vote_with_correct_parameters_and_vote_value_is_image {
                 .post(uri!("/api/vote"))
                 .remote("127.0.0.1:80".parse().unwrap())
                 .json(&json!({
-                    "comparison_id": "33993492-d8ce-4248-a93d-caf88baed82e",
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
                     "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
                     "vote_value": "/static/images/image%20B.png",
                 }))
@@ -206,7 +246,7 @@ mod vote_with_correct_parameters_and_vote_value_is_image {
 
             let expected_created_vote = Vote {
                 id: data.id,
-                comparison_id: uuid!("33993492-d8ce-4248-a93d-caf88baed82e"),
+                comparison_id: uuid!("7d68f7e3-afe5-4d08-9d89-e6905f152eec"),
                 user_id: uuid!("3fa85f64-5717-4562-b3fc-2c963f66afa6"),
                 vote_value: "/static/images/image%20B.png".to_string(),
                 created_at: data.created_at,
@@ -227,6 +267,35 @@ mod vote_with_correct_parameters_and_vote_value_is_image {
 
             assert_ne!(data.id, 42);
         };
+
+        #[test_request]
+        let updates_average_lambda_of_user_in_background = |response| {
+            assert_eq!(response.status(), Status::Created);
+
+            // the refresh runs after the response, so wait for it
+            let mut average_lambda = 0.1234;
+            for _ in 0..50 {
+                rocket::tokio::time::sleep(Duration::from_millis(100)).await;
+
+                let response = client
+                    .get(uri!("/api/user/3fa85f64-5717-4562-b3fc-2c963f66afa6"))
+                    .dispatch()
+                    .await;
+                let json = response.into_json::<ApiResponse<User, ()>>()
+                    .await;
+                average_lambda = json
+                    .expect("json to be preset")
+                    .data
+                    .expect("data to be present")
+                    .average_lambda;
+                if average_lambda != 0.1234 {
+                    break;
+                }
+            }
+
+            assert_ne!(average_lambda, 0.1234);
+            assert!(average_lambda > 0.0 && average_lambda < 1.0);
+        };
     }
 }
 
@@ -274,6 +343,41 @@ mod vote_with_incorrect_comparison_id {
     }
 }
 
+mod vote_with_incorrect_comparison_id_and_vote_value_is_equal {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "44444444-4444-4444-4444-444444444444",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+
+        #[test_request]
+        let returns_expected_error = |response| {
+            let json = response.into_json::<ApiResponse<(), String>>()
+                .await;
+            let error = json
+                .expect("json to be preset")
+                .error
+                .expect("error to be present");
+
+            assert_eq!(error, "`comparison` with requested id not found");
+        };
+    }
+}
+
 mod vote_with_incorrect_user_id {
     use super::*;
 
@@ -361,3 +465,780 @@ mod vote_with_incorrect_vote_value_image {
         };
     }
 }
+
+mod vote_again_on_same_comparison {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "33993492-d8ce-4248-a93d-caf88baed82e",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let returns_409_conflict = |response| {
+            assert_eq!(response.status(), Status::Conflict);
+        };
+
+        #[test_request]
+        let returns_json_error = |response| {
+            let json = response.into_json::<ApiResponse<(), String>>()
+                .await;
+            let error = json
+                .expect("json to be preset")
+                .error
+                .expect("error to be present");
+
+            assert_eq!(error, "`user` already voted on requested `comparison`");
+        };
+    }
+}
+
+mod vote_again_with_repeat_votes_allowed {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes", "repeat_votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "33993492-d8ce-4248-a93d-caf88baed82e",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let numbers_repeat_votes = |response| {
+            let json = response.into_json::<ApiResponse<RepeatedVote, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+            assert_eq!(data.repetition, 1);
+
+            let response = client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "33993492-d8ce-4248-a93d-caf88baed82e",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "different",
+                }))
+                .dispatch()
+                .await;
+            let json = response.into_json::<ApiResponse<RepeatedVote, ()>>()
+                .await;
+            assert_eq!(json.and_then(|json| json.data).map(|data| data.repetition), Some(2));
+        };
+    }
+}
+
+mod vote_on_handed_out_comparison_with_client_metadata {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client.get(uri!("/api/user/3fa85f64-5717-4562-b3fc-2c963f66afa6/comparison"))
+        };
+
+        #[test_request]
+        let records_response_time_and_metadata = |response| {
+            assert_eq!(response.status(), Status::Ok);
+
+            let response = client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                    "viewport_width": 1280,
+                    "viewport_height": 720,
+                    "device_pixel_ratio": 1.5,
+                }))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Created);
+
+            let json = response.into_json::<ApiResponse<TimedVote, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert!(data.response_time_ms.is_some_and(|time| time >= 0));
+            assert_eq!(data.viewport_width, Some(1280));
+            assert_eq!(data.viewport_height, Some(720));
+            assert_eq!(data.device_pixel_ratio, Some(1.5));
+        };
+    }
+}
+
+mod vote_without_handed_out_comparison {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let has_no_response_time = |response| {
+            let json = response.into_json::<ApiResponse<TimedVote, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.response_time_ms, None);
+            assert_eq!(data.viewport_width, None);
+        };
+    }
+}
+
+mod vote_with_incorrect_viewport {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                    "viewport_width": 0,
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod vote_with_confidence {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "/static/images/image%20B.png",
+                    "confidence": 5,
+                }))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let returns_confidence = |response| {
+            let json = response.into_json::<ApiResponse<RatedVote, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.confidence, Some(5));
+        };
+    }
+}
+
+mod vote_with_incorrect_confidence {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "/static/images/image%20B.png",
+                    "confidence": 6,
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+
+        #[test_request]
+        let returns_expected_error = |response| {
+            let json = response.into_json::<ApiResponse<(), String>>()
+                .await;
+            let error = json
+                .expect("json to be preset")
+                .error
+                .expect("error to be present");
+
+            assert_eq!(error, "`confidence` must be between 1 and 5");
+        };
+    }
+}
+
+mod vote_again_with_same_idempotency_key {
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .header(Header::new("Idempotency-Key", "vote-0001"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let returns_original_response = |response| {
+            assert_eq!(response.status(), Status::Created);
+            let body = response.into_string().await.expect("body to be present");
+
+            let response = client
+                .post(uri!("/api/vote"))
+                .header(Header::new("Idempotency-Key", "vote-0001"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Created);
+            assert_eq!(
+                response.into_string().await.expect("body to be present"),
+                body
+            );
+        };
+    }
+}
+
+mod vote_again_with_same_idempotency_key_and_other_vote {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .header(Header::new("Idempotency-Key", "vote-0001"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::Created);
+
+            let response = client
+                .post(uri!("/api/vote"))
+                .header(Header::new("Idempotency-Key", "vote-0001"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "different",
+                }))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod vote_with_idempotency_key_of_other_user {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .header(Header::new("Idempotency-Key", "vote-0001"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let creates_vote_of_other_user = |response| {
+            assert_eq!(response.status(), Status::Created);
+
+            let response = client
+                .post(uri!("/api/vote"))
+                .header(Header::new("Idempotency-Key", "vote-0001"))
+                .json(&json!({
+                    "comparison_id": "67d99e8e-6634-4546-8e26-4c9fb95ba81d",
+                    "user_id": "ac01a03d-75e3-4244-a33b-a2324b8784f1",
+                    "vote_value": "equal",
+                }))
+                .dispatch()
+                .await;
+            assert_eq!(response.status(), Status::Created);
+
+            let json = response.into_json::<rocket::serde::json::Value>().await;
+            let data = json.expect("json to be preset");
+            assert_eq!(data["data"]["user_id"], "ac01a03d-75e3-4244-a33b-a2324b8784f1");
+        };
+    }
+}
+
+mod vote_with_ranking_of_tuple {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "tuple_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "5c1e8f0a-2b7d-4e96-a3f1-c0d9e8b7a6f5",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": [
+                        "/static/images/folder_a/image%203.png",
+                        "/static/images/folder_a/image%201.png",
+                        "/static/images/folder_a/image%202.png",
+                    ],
+                }))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let returns_ranking = |response| {
+            let json = response.into_json::<ApiResponse<RankedVote, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.vote_value, vec![
+                "/static/images/folder_a/image%203.png",
+                "/static/images/folder_a/image%201.png",
+                "/static/images/folder_a/image%202.png",
+            ]);
+        };
+    }
+}
+
+mod vote_with_incomplete_ranking_of_tuple {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "tuple_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "5c1e8f0a-2b7d-4e96-a3f1-c0d9e8b7a6f5",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": [
+                        "/static/images/folder_a/image%203.png",
+                        "/static/images/folder_a/image%203.png",
+                        "/static/images/folder_a/image%202.png",
+                    ],
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+
+        #[test_request]
+        let returns_expected_error = |response| {
+            let json = response.into_json::<ApiResponse<(), String>>()
+                .await;
+            let error = json
+                .expect("json to be preset")
+                .error
+                .expect("error to be present");
+
+            assert_eq!(
+                error,
+                "`vote_value` must rank every image of the `comparison` once"
+            );
+        };
+    }
+}
+
+mod vote_with_best_worst_of_tuple {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "tuple_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "5c1e8f0a-2b7d-4e96-a3f1-c0d9e8b7a6f5",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": {
+                        "best": "/static/images/folder_a/image%202.png",
+                        "worst": "/static/images/folder_a/image%203.png",
+                    },
+                }))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+    }
+}
+
+mod vote_with_best_worst_of_other_comparison {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "tuple_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "5c1e8f0a-2b7d-4e96-a3f1-c0d9e8b7a6f5",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": {
+                        "best": "/static/images/folder_a/image%202.png",
+                        "worst": "/static/images/image%20A.png",
+                    },
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod vote_with_same_best_and_worst {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "tuple_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "5c1e8f0a-2b7d-4e96-a3f1-c0d9e8b7a6f5",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": {
+                        "best": "/static/images/folder_a/image%202.png",
+                        "worst": "/static/images/folder_a/image%202.png",
+                    },
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod vote_with_rating_of_single_image {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "rating_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "2e4f6a8c-0b1d-4f3e-9a7c-5b3d1f0e2a4c",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": 5,
+                }))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let returns_rating = |response| {
+            let json = response.into_json::<ApiResponse<RatingVote, ()>>()
+                .await;
+            let data = json
+                .expect("json to be preset")
+                .data
+                .expect("data to be present");
+
+            assert_eq!(data.vote_value, 5);
+        };
+    }
+}
+
+mod vote_with_rating_outside_scale {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "rating_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "2e4f6a8c-0b1d-4f3e-9a7c-5b3d1f0e2a4c",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": 6,
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+
+        #[test_request]
+        let returns_expected_error = |response| {
+            let json = response.into_json::<ApiResponse<(), String>>()
+                .await;
+            let error = json
+                .expect("json to be preset")
+                .error
+                .expect("error to be present");
+
+            assert_eq!(error, "`vote_value` must be between 1 and 5");
+        };
+    }
+}
+
+mod vote_with_equal_on_single_image {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "rating_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "2e4f6a8c-0b1d-4f3e-9a7c-5b3d1f0e2a4c",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "equal",
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod vote_with_rating_of_pair {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "rating_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": 3,
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+    }
+}
+
+mod vote_with_enabled_cannot_tell {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "answers")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "cannot_tell",
+                }))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+    }
+}
+
+mod vote_with_disabled_answer {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "answers")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "7d68f7e3-afe5-4d08-9d89-e6905f152eec",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "different",
+                }))
+        };
+
+        #[test_request]
+        let returns_422_unprocessable_entity = |response| {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        };
+
+        #[test_request]
+        let returns_expected_error = |response| {
+            let json = response.into_json::<ApiResponse<(), String>>()
+                .await;
+            let error = json
+                .expect("json to be preset")
+                .error
+                .expect("error to be present");
+
+            assert_eq!(
+                error,
+                "`different` is not an answer enabled for the dirname"
+            );
+        };
+    }
+}
+
+mod vote_on_gold_comparison_with_expected_answer {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    make_api_test! {
+        #[fileserver(static_dir = relative!("tests/static_dir/ok"))]
+        #[fixtures("admins", "users", "comparisons", "votes", "gold_comparisons")]
+        let request = |client| {
+            client
+                .post(uri!("/api/vote"))
+                .json(&json!({
+                    "comparison_id": "9b8a7c6d-5e4f-4031-8a2b-1c0d9e8f7a6b",
+                    "user_id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "vote_value": "/static/images/image%20A.png",
+                }))
+        };
+
+        #[test_request]
+        let returns_201_created = |response| {
+            assert_eq!(response.status(), Status::Created);
+        };
+
+        #[test_request]
+        let updates_reliability_of_user = |response| {
+            assert_eq!(response.status(), Status::Created);
+
+            let json = client
+                .get(uri!("/api/user/3fa85f64-5717-4562-b3fc-2c963f66afa6"))
+                .dispatch()
+                .await
+                .into_json::<ApiResponse<User, ()>>()
+                .await;
+            let reliability = json
+                .and_then(|json| json.data)
+                .and_then(|user| user.reliability);
+
+            assert_eq!(reliability, Some(1.0));
+        };
+    }
+}